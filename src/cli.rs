@@ -1,4 +1,6 @@
-use clap::{Arg, Command};
+use crate::sudoku::{parse_board, Difficulty};
+
+use clap::{value_parser, Arg, ArgAction, Command};
 
 pub fn new() -> Command {
     Command::new("shdoku")
@@ -10,4 +12,151 @@ pub fn new() -> Command {
                 .long("difficulty")
                 .help("Defined difficulty levels or a custom number of blank spaces"),
         )
+        .arg(
+            Arg::new("graded")
+                .short('g')
+                .long("graded")
+                .action(ArgAction::SetTrue)
+                .help("Pick the sudoku by the solving techniques it needs instead of its blank spaces"),
+        )
+        .arg(
+            Arg::new("seed")
+                .short('s')
+                .long("seed")
+                .value_name("number")
+                .value_parser(value_parser!(u64))
+                .help("Seed to generate the sudoku from, to replay or share a puzzle"),
+        )
+        .arg(
+            Arg::new("puzzle")
+                .short('p')
+                .long("puzzle")
+                .value_name("81 cells")
+                .value_parser(parse_board)
+                .conflicts_with_all(["difficulty", "graded", "seed"])
+                .help("Play the given puzzle, with cells listed row by row and 0 or . for blanks"),
+        )
+        .arg(
+            Arg::new("output")
+                .short('o')
+                .long("output")
+                .value_name("file")
+                .help("File to export the board to with `w`. By default it is printed when quitting"),
+        )
+        .arg(
+            Arg::new("check")
+                .short('c')
+                .long("check")
+                .action(ArgAction::SetTrue)
+                .help("Highlight numbers that disagree with the solution and count them as mistakes"),
+        )
+        .arg(
+            Arg::new("strikes")
+                .long("strikes")
+                .action(ArgAction::SetTrue)
+                .help("End the game after three mistakes. Implies --check"),
+        )
+        .arg(
+            Arg::new("resume")
+                .short('r')
+                .long("resume")
+                .action(ArgAction::SetTrue)
+                .conflicts_with_all(["difficulty", "graded", "seed", "puzzle", "strikes"])
+                .help("Resume the game saved when quitting the last time"),
+        )
+        .args_conflicts_with_subcommands(true)
+        .subcommand(
+            Command::new("solve")
+                .about("Print the solutions of puzzles given one per line in the 81-character format")
+                .arg(
+                    Arg::new("file")
+                        .value_name("file")
+                        .help("File to read the puzzles from. Reads standard input if omitted or -"),
+                )
+                .arg(
+                    Arg::new("grid")
+                        .short('g')
+                        .long("grid")
+                        .action(ArgAction::SetTrue)
+                        .help("Print the solutions as grids instead of lines"),
+                ),
+        )
+        .subcommand(
+            Command::new("generate")
+                .about("Print unique puzzles in the 81-character format, one per line")
+                .arg(
+                    Arg::new("count")
+                        .short('n')
+                        .long("count")
+                        .value_name("number")
+                        .value_parser(value_parser!(usize))
+                        .default_value("1")
+                        .help("Number of puzzles to generate"),
+                )
+                .arg(
+                    Arg::new("difficulty")
+                        .short('d')
+                        .long("difficulty")
+                        .value_name("easy|mid|hard|expert|0..81")
+                        .value_parser(|s: &str| s.parse::<Difficulty>())
+                        .default_value("mid")
+                        .help("Defined difficulty levels or a custom number of blank spaces"),
+                )
+                .arg(
+                    Arg::new("graded")
+                        .short('g')
+                        .long("graded")
+                        .action(ArgAction::SetTrue)
+                        .help("Pick the puzzles by the solving techniques they need instead of their blank spaces"),
+                )
+                .arg(
+                    Arg::new("seed")
+                        .short('s')
+                        .long("seed")
+                        .value_name("number")
+                        .value_parser(value_parser!(u64))
+                        .help("Seed to generate the puzzles from, to reproduce a pack"),
+                )
+                .arg(
+                    Arg::new("solutions")
+                        .long("solutions")
+                        .action(ArgAction::SetTrue)
+                        .help("Print the solution after each puzzle"),
+                )
+                .arg(
+                    Arg::new("grades")
+                        .long("grades")
+                        .action(ArgAction::SetTrue)
+                        .help("Print the grade of each puzzle, by the solving techniques it needs"),
+                ),
+        )
+        .subcommand(
+            Command::new("scores")
+                .about("Print the best times of won games per difficulty")
+                .arg(
+                    Arg::new("difficulty")
+                        .short('d')
+                        .long("difficulty")
+                        .value_name("easy|mid|hard|expert|0..81")
+                        .value_parser(|s: &str| s.parse::<Difficulty>())
+                        .help("Only print the scores of this difficulty"),
+                ),
+        )
+        .subcommand(
+            Command::new("validate")
+                .about("Check puzzles given one per line in the 81-character format for conflicts and unique solutions")
+                .arg(
+                    Arg::new("file")
+                        .value_name("file")
+                        .help("File to read the puzzles from. Reads standard input if omitted or -"),
+                )
+                .after_help(
+                    "Exit codes (the highest one of all puzzles):\n  \
+                     0  every puzzle has a unique solution\n  \
+                     1  a puzzle could not be read\n  \
+                     2  a puzzle has more than one solution\n  \
+                     3  a puzzle has no solution\n  \
+                     4  a puzzle has numbers breaking the rules",
+                ),
+        )
 }
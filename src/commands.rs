@@ -0,0 +1,214 @@
+use crate::rand::{Rng, SeedableRng};
+use crate::rand_chacha::ChaCha8Rng;
+use crate::scores;
+use crate::sudoku::{self, *};
+
+use clap::ArgMatches;
+
+use std::{
+    collections::HashSet,
+    fs, io,
+    io::{Read, Write},
+};
+
+/// the `solve` subcommand: prints the solution of every puzzle
+/// in the input, one per line, or as grids with `--grid`.
+///
+/// returns the exit code, which is 1 if any puzzle could not be solved.
+pub fn solve(args: &ArgMatches) -> i32 {
+    let input = match read_input(args.get_one::<String>("file")) {
+        Ok(input) => input,
+        Err(e) => {
+            eprintln!("[!]: Error: {e}");
+            return 1;
+        }
+    };
+
+    let mut exit_code = 0;
+
+    for (line_nr, line) in puzzle_lines(&input) {
+        let board = match parse_board(line) {
+            Ok(board) => board,
+            Err(e) => {
+                eprintln!("[!]: Error: line {line_nr}: {e}");
+                exit_code = 1;
+                continue;
+            }
+        };
+
+        let (count, solutions) = count_solutions(&board, 2);
+        if count == SolutionCount::Many {
+            eprintln!("[!]: Warning: line {line_nr}: The puzzle has more than one solution.");
+        }
+
+        match (solutions.first(), args.get_flag("grid")) {
+            (Some(solution), true) => print!("{}", format_grid(solution)),
+            (Some(solution), false) => println!("{}", format_line(solution)),
+            (None, _) => {
+                eprintln!("[!]: Error: line {line_nr}: The puzzle has no solution.");
+                exit_code = 1;
+            }
+        }
+    }
+
+    exit_code
+}
+
+/// the `generate` subcommand: prints `--count` different puzzles, one per line,
+/// followed by their solution and grade if requested.
+///
+/// every puzzle is generated from its own seed, drawn from the `--seed` of the pack.
+///
+/// NOTE: stops without an error once the output is closed, like when piped into `head`.
+pub fn generate(args: &ArgMatches) -> i32 {
+    let count = *args.get_one::<usize>("count").unwrap_or(&1);
+    let difficulty = *args
+        .get_one::<Difficulty>("difficulty")
+        .unwrap_or(&Difficulty::Mid);
+    let seed = args
+        .get_one::<u64>("seed")
+        .copied()
+        .unwrap_or_else(random_seed);
+
+    let mut rng = ChaCha8Rng::seed_from_u64(seed);
+    let mut puzzles = HashSet::with_capacity(count);
+    let mut stdout = io::stdout().lock();
+
+    while puzzles.len() < count {
+        let puzzle_seed = rng.gen();
+        let puzzle = match args.get_flag("graded") {
+            true => generate_graded_sudoku(puzzle_seed, difficulty),
+            false => generate_sudoku(puzzle_seed, difficulty),
+        };
+        if !puzzles.insert(puzzle) {
+            continue;
+        }
+
+        let mut line = format_line(&puzzle);
+        if args.get_flag("solutions") {
+            let (_, solutions) = count_solutions(&puzzle, 1);
+            line.push(' ');
+            line.push_str(&format_line(&solutions[0]));
+        }
+        if args.get_flag("grades") {
+            let grade = match grade(&puzzle) {
+                Some(grade) => grade.to_string(),
+                None => String::from("Unrated"),
+            };
+            line.push(' ');
+            line.push_str(&grade);
+        }
+        match writeln!(stdout, "{line}") {
+            Ok(_) => {}
+            Err(e) if e.kind() == io::ErrorKind::BrokenPipe => return 0,
+            Err(e) => {
+                eprintln!("[!]: Error: {e}");
+                return 1;
+            }
+        }
+    }
+
+    0
+}
+
+/// the `validate` subcommand: prints the conflicts of every puzzle in the input
+/// and whether it has a unique solution.
+///
+/// returns the highest exit code of all puzzles, see the `validate` help in `cli::new()`.
+pub fn validate(args: &ArgMatches) -> i32 {
+    let input = match read_input(args.get_one::<String>("file")) {
+        Ok(input) => input,
+        Err(e) => {
+            eprintln!("[!]: Error: {e}");
+            return 1;
+        }
+    };
+
+    let mut exit_code = 0;
+
+    for (line_nr, line) in puzzle_lines(&input) {
+        let board = match parse_line(line) {
+            Ok(board) => board,
+            Err(e) => {
+                eprintln!("[!]: Error: line {line_nr}: {e}");
+                exit_code = exit_code.max(1);
+                continue;
+            }
+        };
+
+        let validation = sudoku::validate(&board);
+        let (code, result) = match validation.solutions {
+            _ if !validation.conflicts.is_empty() => (4, "breaks the rules"),
+            SolutionCount::Zero => (3, "no solution"),
+            SolutionCount::Many => (2, "multiple solutions"),
+            SolutionCount::One => (0, "unique solution"),
+        };
+        exit_code = exit_code.max(code);
+
+        println!("line {line_nr}: {result}");
+        for conflict in validation.conflicts {
+            println!("  {conflict}");
+        }
+    }
+
+    exit_code
+}
+
+/// reads the contents of the file at `path`,
+/// or standard input if `path` is `None` or `-`.
+fn read_input(path: Option<&String>) -> Result<String, String> {
+    match path.map(String::as_str) {
+        None | Some("-") => {
+            let mut input = String::new();
+            io::stdin()
+                .read_to_string(&mut input)
+                .map_err(|e| format!("Failed to read standard input: {e}"))?;
+            Ok(input)
+        }
+        Some(path) => fs::read_to_string(path).map_err(|e| format!("Failed to read {path}: {e}")),
+    }
+}
+
+/// returns the non-empty lines of `input` with their 1-based line numbers,
+/// skipping comments starting with `#`.
+pub fn puzzle_lines(input: &str) -> impl Iterator<Item = (usize, &str)> {
+    input
+        .lines()
+        .enumerate()
+        .map(|(i, line)| (i + 1, line.trim()))
+        .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+}
+
+/// the `scores` subcommand: prints the highscore table of every difficulty
+/// with scores, or only of `--difficulty`.
+///
+/// returns the exit code, which is 1 if the highscores could not be read.
+pub fn scores(args: &ArgMatches) -> i32 {
+    let scores = match scores::default_path().map(|path| scores::read(&path)) {
+        Some(Ok(scores)) => scores,
+        Some(Err(e)) => {
+            eprintln!("[!]: Error: {e}");
+            return 1;
+        }
+        None => Vec::new(),
+    };
+
+    let difficulties = match args.get_one::<Difficulty>("difficulty") {
+        Some(&difficulty) => vec![difficulty],
+        None => scores::difficulties(&scores),
+    };
+    let tables = difficulties
+        .into_iter()
+        .filter(|&difficulty| !scores::top(&scores, difficulty).is_empty())
+        .map(|difficulty| {
+            let table = scores::table(&scores, difficulty);
+            format!("{difficulty}\n{}\n", table.join("\n"))
+        })
+        .collect::<Vec<_>>();
+
+    match tables.is_empty() {
+        true => println!("No highscores yet."),
+        false => print!("{}", tables.join("\n")),
+    }
+    0
+}
@@ -0,0 +1,236 @@
+use crate::keys::*;
+use crate::paths;
+use crate::theme::Theme;
+
+use serde::de::{self, DeserializeSeed, Deserializer, MapAccess, SeqAccess, Visitor};
+use serde::Deserialize;
+use toml::Spanned;
+
+use std::{fmt, fs, path::PathBuf};
+
+/// the settings read from the config file.
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct Config {
+    pub keymap: Keymap,
+    pub theme: Theme,
+}
+
+/// returns the path of the config file in the config directory.
+pub fn default_path() -> Option<PathBuf> {
+    paths::config_dir().map(|dir| dir.join("config.toml"))
+}
+
+/// reads the config file, using the defaults if there is none.
+pub fn load() -> Result<Config, String> {
+    let path = match default_path() {
+        Some(path) if path.exists() => path,
+        _ => return Ok(Config::default()),
+    };
+    let text =
+        fs::read_to_string(&path).map_err(|e| format!("Failed to read {}: {e}", path.display()))?;
+    parse(&text).map_err(|e| format!("{}: {e}", path.display()))
+}
+
+/// parses a config file written in toml:
+///
+/// ```toml
+/// # the keymap to start from: vim, arrows or wasd
+/// preset = "arrows"
+/// # the colors, see `THEMES`
+/// theme = "light"
+///
+/// # actions bound in all modes
+/// [keys]
+/// move_left = ["h", "Left"]
+/// hint = "?"
+///
+/// # actions bound in Go and Visual mode
+/// [keys.go]
+/// list_branches = "b"
+/// [keys.visual]
+/// paint_selection = "p"
+/// ```
+///
+/// NOTE: binding an action replaces the keys it had in the preset,
+///       an empty list unbinds it.
+pub fn parse(text: &str) -> Result<Config, String> {
+    // the line of the position `at` in `text`
+    let line = |at: usize| text[..at].lines().count().max(1);
+    let at_line = |at: usize| move |e: String| format!("line {}: {e}", line(at));
+
+    let file = toml::from_str::<ConfigFile>(text).map_err(|e| {
+        let message = e.message().trim_end();
+        match e.span() {
+            Some(span) => at_line(span.start)(message.to_string()),
+            None => message.to_string(),
+        }
+    })?;
+
+    let mut keymap = match file.preset {
+        Some(name) => Keymap::preset(name.get_ref()).map_err(at_line(name.span().start))?,
+        None => Keymap::default(),
+    };
+    for (section, action, keys) in file.keys.0 {
+        let action = action
+            .get_ref()
+            .parse::<Action>()
+            .map_err(at_line(action.span().start))?;
+        let names = match keys.get_ref() {
+            KeyNames::One(name) => vec![(name.as_str(), keys.span())],
+            KeyNames::Many(names) => names
+                .iter()
+                .map(|name| (name.get_ref().as_str(), name.span()))
+                .collect(),
+        };
+        let keys = names
+            .into_iter()
+            .map(|(name, span)| bindable_key(name).map_err(at_line(span.start)))
+            .collect::<Result<Vec<Key>, _>>()?;
+        keymap.bind(section, action, &keys);
+    }
+
+    let theme = match file.theme {
+        Some(name) => Theme::by_name(name.get_ref()).map_err(at_line(name.span().start))?,
+        None => Theme::default(),
+    };
+
+    Ok(Config { keymap, theme })
+}
+
+/// sets the theme in the config file to `theme`, keeping everything else.
+/// creates the file if there is none.
+pub fn save_theme(theme: &Theme) -> Result<(), String> {
+    let path = default_path().ok_or("No config directory to save the theme in.")?;
+    let text = match path.exists() {
+        true => fs::read_to_string(&path)
+            .map_err(|e| format!("Failed to read {}: {e}", path.display()))?,
+        false => String::new(),
+    };
+
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|e| format!("Failed to create {}: {e}", dir.display()))?;
+    }
+    fs::write(&path, with_theme(&text, theme))
+        .map_err(|e| format!("Failed to write {}: {e}", path.display()))
+}
+
+/// returns the config file `text` with its theme set to `theme`,
+/// replacing the value of the `theme` setting or adding one at the top.
+pub fn with_theme(text: &str, theme: &Theme) -> String {
+    #[derive(Deserialize)]
+    struct ThemeSetting {
+        theme: Option<Spanned<String>>,
+    }
+
+    let value = format!("\"{}\"", theme.name);
+    match toml::from_str::<ThemeSetting>(text)
+        .ok()
+        .and_then(|file| file.theme)
+    {
+        Some(old) => {
+            let span = old.span();
+            format!("{}{value}{}", &text[..span.start], &text[span.end..])
+        }
+        None => format!("theme = {value}\n{text}"),
+    }
+}
+
+/// parses the name of a key an action can be bound to.
+///
+/// NOTE: the digits 1-9 always enter numbers or counts.
+fn bindable_key(name: &str) -> Result<Key, String> {
+    match name.parse()? {
+        Key::Char('1'..='9') => Err(format!("The digits 1-9 can't be rebound, found '{name}'")),
+        key => Ok(key),
+    }
+}
+
+/// the config file as written, checked by `parse()`.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ConfigFile {
+    preset: Option<Spanned<String>>,
+    theme: Option<Spanned<String>>,
+    #[serde(default)]
+    keys: Bindings,
+}
+
+/// the names of the actions and keys in the `[keys]` tables, in the order they are written.
+#[derive(Default)]
+struct Bindings(Vec<(KeymapSection, Spanned<String>, Spanned<KeyNames>)>);
+
+impl<'de> Deserialize<'de> for Bindings {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_map(BindingsVisitor(KeymapSection::Global))
+    }
+}
+
+/// reads the bindings of a section, and those of `[keys.go]`
+/// and `[keys.visual]` nested in the global one.
+struct BindingsVisitor(KeymapSection);
+
+impl<'de> Visitor<'de> for BindingsVisitor {
+    type Value = Bindings;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "a table of actions and their keys")
+    }
+
+    fn visit_map<M: MapAccess<'de>>(self, mut map: M) -> Result<Bindings, M::Error> {
+        let mut bindings = Vec::new();
+        while let Some(name) = map.next_key::<Spanned<String>>()? {
+            let section = match (self.0, name.get_ref().as_str()) {
+                (KeymapSection::Global, "go") => Some(KeymapSection::Go),
+                (KeymapSection::Global, "visual") => Some(KeymapSection::Visual),
+                _ => None,
+            };
+            match section {
+                Some(section) => bindings.extend(map.next_value_seed(BindingsVisitor(section))?.0),
+                None => bindings.push((self.0, name, map.next_value()?)),
+            }
+        }
+        Ok(Bindings(bindings))
+    }
+}
+
+impl<'de> DeserializeSeed<'de> for BindingsVisitor {
+    type Value = Bindings;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Bindings, D::Error> {
+        deserializer.deserialize_map(self)
+    }
+}
+
+/// the keys bound to an action, written as a single key or a list of them.
+enum KeyNames {
+    One(String),
+    Many(Vec<Spanned<String>>),
+}
+
+impl<'de> Deserialize<'de> for KeyNames {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(KeyNamesVisitor)
+    }
+}
+
+struct KeyNamesVisitor;
+
+impl<'de> Visitor<'de> for KeyNamesVisitor {
+    type Value = KeyNames;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "a key or a list of keys")
+    }
+
+    fn visit_str<E: de::Error>(self, name: &str) -> Result<KeyNames, E> {
+        Ok(KeyNames::One(name.to_string()))
+    }
+
+    fn visit_seq<S: SeqAccess<'de>>(self, mut seq: S) -> Result<KeyNames, S::Error> {
+        let mut names = Vec::new();
+        while let Some(name) = seq.next_element()? {
+            names.push(name);
+        }
+        Ok(KeyNames::Many(names))
+    }
+}
@@ -0,0 +1,139 @@
+use crate::commands::puzzle_lines;
+use crate::save;
+use crate::state::*;
+use crate::sudoku::*;
+
+use std::{fs, path::Path};
+
+/// a command entered on the `:` command line, like in vim.
+#[derive(Debug, PartialEq)]
+pub enum ExCommand {
+    /// `:new [difficulty]`, a new game of the given or current difficulty
+    New(Option<Difficulty>),
+    /// `:w [file]`, saves the game to `file` or where it is saved when quitting
+    Write(Option<String>),
+    /// `:e file`, plays a saved game or the first puzzle in `file`
+    Edit(String),
+    /// `:seed [number]`, shows the seed of the game or plays the one generated from `number`
+    Seed(Option<u64>),
+    /// `:hint`
+    Hint,
+    /// `:check`, counts the numbers that disagree with the solution
+    Check,
+    /// `:theme [name]`, switches to the theme or lets the player choose one
+    Theme(Option<String>),
+    /// `:q`, or `:q!` to quit without saving
+    Quit { save: bool },
+}
+
+/// parses a line entered on the command line, without the leading `:`.
+pub fn parse(line: &str) -> Result<ExCommand, String> {
+    let mut words = line.split_whitespace();
+    let command = words.next().unwrap_or("");
+    let arg = words.next();
+
+    if let Some(extra) = words.next() {
+        return Err(format!("Too many arguments: {extra}"));
+    }
+
+    match (command, arg) {
+        ("new", arg) => match arg.map(str::parse).transpose() {
+            Ok(difficulty) => Ok(ExCommand::New(difficulty)),
+            Err(_) => Err(format!("Invalid difficulty: {}", arg.unwrap_or(""))),
+        },
+        ("w" | "write", arg) => Ok(ExCommand::Write(arg.map(String::from))),
+        ("e" | "edit", Some(file)) => Ok(ExCommand::Edit(file.to_string())),
+        ("e" | "edit", None) => Err(String::from("Missing file name")),
+        ("seed", arg) => match arg.map(str::parse).transpose() {
+            Ok(seed) => Ok(ExCommand::Seed(seed)),
+            Err(_) => Err(format!("Invalid seed: {}", arg.unwrap_or(""))),
+        },
+        ("theme", arg) => Ok(ExCommand::Theme(arg.map(String::from))),
+        ("hint", None) => Ok(ExCommand::Hint),
+        ("check", None) => Ok(ExCommand::Check),
+        ("q" | "quit", None) => Ok(ExCommand::Quit { save: true }),
+        ("q!" | "quit!", None) => Ok(ExCommand::Quit { save: false }),
+        ("hint" | "check" | "q" | "quit" | "q!" | "quit!", Some(arg)) => {
+            Err(format!("Too many arguments: {arg}"))
+        }
+        ("", _) => Err(String::from("No command")),
+        (command, _) => Err(format!("Not a command: {command}")),
+    }
+}
+
+/// runs `command`, leaving its result in `state.message`.
+/// new games are graded by solving techniques if `graded` is set.
+///
+/// NOTE: `ExCommand::Quit` and `ExCommand::Theme` have to be handled by the caller.
+pub fn run(command: ExCommand, state: &mut State, graded: bool) -> Result<(), String> {
+    match command {
+        ExCommand::New(difficulty) => {
+            let difficulty = difficulty.unwrap_or(state.difficulty);
+            replace_game(state, State::new_game(difficulty, random_seed(), graded));
+            state.message = format!("New {difficulty} game");
+        }
+        ExCommand::Write(file) => {
+            let path = match file {
+                Some(file) => Path::new(&file).to_path_buf(),
+                None => save::default_path().ok_or("No data directory to save the game in.")?,
+            };
+            save::write(state, &path)?;
+            state.message = format!("Saved to {}", path.display());
+        }
+        ExCommand::Edit(file) => {
+            replace_game(state, open(&file)?);
+            state.message = format!("Opened {file}");
+        }
+        ExCommand::Seed(None) => {
+            state.message = match (state.seed, state.replay_command()) {
+                (Some(seed), Some(command)) => format!("Seed: {seed}, replay with `{command}`"),
+                _ => String::from("This puzzle was not generated from a seed"),
+            };
+        }
+        ExCommand::Seed(Some(seed)) => {
+            let difficulty = state.difficulty;
+            replace_game(state, State::new_game(difficulty, seed, graded));
+            state.message = format!("New {difficulty} game from seed {seed}");
+        }
+        ExCommand::Hint => state.hint(),
+        ExCommand::Check => {
+            let solution = state
+                .solution
+                .ok_or("Can't check, the puzzle has no unique solution")?;
+            let wrong = all_cells()
+                .filter(|&(r, c)| state.board[r][c] != 0 && state.board[r][c] != solution[r][c])
+                .count();
+            state.message = match wrong {
+                0 => String::from("No mistakes so far"),
+                1 => String::from("1 number disagrees with the solution"),
+                n => format!("{n} numbers disagree with the solution"),
+            };
+        }
+        ExCommand::Quit { .. } | ExCommand::Theme(_) => {}
+    }
+    Ok(())
+}
+
+/// reads the game saved in `file`, or the first puzzle in it.
+pub fn open(file: &str) -> Result<State, String> {
+    let text = fs::read_to_string(file).map_err(|e| format!("Failed to read {file}: {e}"))?;
+    match text.starts_with("shdoku-save") {
+        true => save::deserialize(&text),
+        false => {
+            let (_, line) = puzzle_lines(&text)
+                .next()
+                .ok_or(format!("No puzzle in {file}"))?;
+            let line = line.strip_prefix("puzzle:").unwrap_or(line).trim();
+            Ok(State::from_puzzle(parse_board(line)?))
+        }
+    }
+}
+
+/// replaces the game in `state` with `game`,
+/// keeping the settings the player chose for highlighting and checking.
+fn replace_game(state: &mut State, mut game: State) {
+    game.highlight_conflicts = state.highlight_conflicts;
+    game.max_mistakes = game.max_mistakes.or(state.max_mistakes);
+    game.check_mistakes = (game.check_mistakes || state.check_mistakes) && game.solution.is_some();
+    *state = game;
+}
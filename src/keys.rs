@@ -0,0 +1,426 @@
+use crate::state::Mode;
+
+use crossterm::event::KeyCode;
+
+use std::collections::HashMap;
+
+/// a key that can be bound to an `Action`.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum Key {
+    Char(char),
+    Left,
+    Right,
+    Up,
+    Down,
+    Home,
+    End,
+    PageUp,
+    PageDown,
+    Enter,
+    Esc,
+    Backspace,
+    Tab,
+}
+
+impl Key {
+    /// returns the `Key` of a terminal key event, if it can be bound.
+    pub fn from_code(code: KeyCode) -> Option<Key> {
+        match code {
+            KeyCode::Char(c) => Some(Key::Char(c)),
+            KeyCode::Left => Some(Key::Left),
+            KeyCode::Right => Some(Key::Right),
+            KeyCode::Up => Some(Key::Up),
+            KeyCode::Down => Some(Key::Down),
+            KeyCode::Home => Some(Key::Home),
+            KeyCode::End => Some(Key::End),
+            KeyCode::PageUp => Some(Key::PageUp),
+            KeyCode::PageDown => Some(Key::PageDown),
+            KeyCode::Enter => Some(Key::Enter),
+            KeyCode::Esc => Some(Key::Esc),
+            KeyCode::Backspace => Some(Key::Backspace),
+            KeyCode::Tab => Some(Key::Tab),
+            _ => None,
+        }
+    }
+}
+
+impl std::str::FromStr for Key {
+    type Err = String;
+    /// parses a single character, `Space`, or the name of a special key like `Left`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut chars = s.chars();
+        if let (Some(c), None) = (chars.next(), chars.next()) {
+            return Ok(Key::Char(c));
+        }
+        match s.to_lowercase().as_str() {
+            "space" => Ok(Key::Char(' ')),
+            "left" => Ok(Key::Left),
+            "right" => Ok(Key::Right),
+            "up" => Ok(Key::Up),
+            "down" => Ok(Key::Down),
+            "home" => Ok(Key::Home),
+            "end" => Ok(Key::End),
+            "pageup" => Ok(Key::PageUp),
+            "pagedown" => Ok(Key::PageDown),
+            "enter" => Ok(Key::Enter),
+            "esc" => Ok(Key::Esc),
+            "backspace" => Ok(Key::Backspace),
+            "tab" => Ok(Key::Tab),
+            _ => Err(format!("Unknown key '{s}'")),
+        }
+    }
+}
+
+/// everything a key can be bound to.
+///
+/// NOTE: the digits `1-9` always preselect numbers (or count),
+///       and are not bound to actions.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Action {
+    MoveLeft,
+    MoveDown,
+    MoveUp,
+    MoveRight,
+    MoveFarLeft,
+    MoveFarDown,
+    MoveFarUp,
+    MoveFarRight,
+
+    EditMode,
+    EditModeOnce,
+    MarkupMode,
+    MarkupModeOnce,
+    GoMode,
+    VisualMode,
+
+    /// places/unplaces the preselected number or mark,
+    /// marks the selection in Visual mode
+    Toggle,
+    /// deletes a number or mark,
+    /// deletes the marks of the selection in Visual mode
+    Delete,
+
+    UndoChronologically,
+    RedoChronologically,
+    ListBranches,
+    StampSelection,
+    PaintSelection,
+
+    Hint,
+    RepeatChange,
+    FillCandidates,
+    FillCurrentCandidates,
+    ToggleConflicts,
+    ToggleMistakes,
+    Undo,
+    Redo,
+    Export,
+    SetCheckpoint,
+    JumpToCheckpoint,
+    CommandLine,
+    Quit,
+}
+
+/// the names of all actions as used in the config file.
+const ACTION_NAMES: [(&str, Action); 34] = [
+    ("move_left", Action::MoveLeft),
+    ("move_down", Action::MoveDown),
+    ("move_up", Action::MoveUp),
+    ("move_right", Action::MoveRight),
+    ("move_far_left", Action::MoveFarLeft),
+    ("move_far_down", Action::MoveFarDown),
+    ("move_far_up", Action::MoveFarUp),
+    ("move_far_right", Action::MoveFarRight),
+    ("edit_mode", Action::EditMode),
+    ("edit_mode_once", Action::EditModeOnce),
+    ("markup_mode", Action::MarkupMode),
+    ("markup_mode_once", Action::MarkupModeOnce),
+    ("go_mode", Action::GoMode),
+    ("visual_mode", Action::VisualMode),
+    ("toggle", Action::Toggle),
+    ("delete", Action::Delete),
+    ("undo_chronologically", Action::UndoChronologically),
+    ("redo_chronologically", Action::RedoChronologically),
+    ("list_branches", Action::ListBranches),
+    ("stamp_selection", Action::StampSelection),
+    ("paint_selection", Action::PaintSelection),
+    ("hint", Action::Hint),
+    ("repeat_change", Action::RepeatChange),
+    ("fill_candidates", Action::FillCandidates),
+    ("fill_current_candidates", Action::FillCurrentCandidates),
+    ("toggle_conflicts", Action::ToggleConflicts),
+    ("toggle_mistakes", Action::ToggleMistakes),
+    ("undo", Action::Undo),
+    ("redo", Action::Redo),
+    ("export", Action::Export),
+    ("set_checkpoint", Action::SetCheckpoint),
+    ("jump_to_checkpoint", Action::JumpToCheckpoint),
+    ("command_line", Action::CommandLine),
+    ("quit", Action::Quit),
+];
+
+impl Action {
+    /// returns whether a count typed before the action repeats it.
+    pub fn is_countable(&self) -> bool {
+        use Action::*;
+        matches!(
+            self,
+            MoveLeft
+                | MoveDown
+                | MoveUp
+                | MoveRight
+                | MoveFarLeft
+                | MoveFarDown
+                | MoveFarUp
+                | MoveFarRight
+                | Toggle
+                | UndoChronologically
+                | RedoChronologically
+                | Hint
+                | Undo
+                | Redo
+        )
+    }
+}
+
+impl std::str::FromStr for Action {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        ACTION_NAMES
+            .iter()
+            .find(|(name, _)| *name == s)
+            .map(|&(_, action)| action)
+            .ok_or(format!("Unknown action '{s}'"))
+    }
+}
+
+/// the digits typed before a motion or action, like vim's counts.
+///
+/// a single digit only preselects its number, so `5jj<space>` places 5
+/// two rows down. two or more digits form a count, like `10j` or `03l`,
+/// and the preselection goes back to the one from before the count.
+/// before `<space>`, the last digit is the number and the ones
+/// before it are the count, like `35<space>` placing 5 in three cells.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Count {
+    digits: Vec<u8>,
+    /// the preselection from before the first digit
+    preselection: u8,
+}
+
+impl Count {
+    /// adds a typed digit, given the preselection before it.
+    pub fn push(&mut self, digit: u8, preselection: u8) {
+        if self.digits.is_empty() {
+            self.preselection = preselection;
+        }
+        self.digits.push(digit);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.digits.is_empty()
+    }
+
+    /// clears the digits, returning how often `action` is repeated
+    /// and the number to preselect before it, if it changes.
+    ///
+    /// NOTE: counts are capped at 999.
+    pub fn take(&mut self, action: Option<Action>) -> (usize, Option<u8>) {
+        let digits = std::mem::take(&mut self.digits);
+        let value = |digits: &[u8]| {
+            digits
+                .iter()
+                .fold(0, |count, &digit| (count * 10 + digit as usize).min(999))
+        };
+        match (action, digits.split_last()) {
+            (_, None) => (1, None),
+            (Some(Action::Toggle), Some((&number, count))) if number != 0 => {
+                (value(count).max(1), None)
+            }
+            (Some(action), Some((_, rest))) if action.is_countable() && !rest.is_empty() => {
+                (value(&digits).max(1), Some(self.preselection))
+            }
+            _ => (1, None),
+        }
+    }
+}
+
+/// the part of a `Keymap` that applies in some modes.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum KeymapSection {
+    /// bindings for all modes
+    Global,
+    /// bindings for Go mode, taking precedence over the global ones
+    Go,
+    /// bindings for Visual mode, taking precedence over the global ones
+    Visual,
+}
+
+/// maps keys to actions.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Keymap {
+    global: HashMap<Key, Action>,
+    go: HashMap<Key, Action>,
+    visual: HashMap<Key, Action>,
+}
+
+impl Keymap {
+    /// returns the keymap of a preset by name: `vim`, `arrows` or `wasd`.
+    pub fn preset(name: &str) -> Result<Keymap, String> {
+        match name {
+            "vim" => Ok(Keymap::vim()),
+            "arrows" => Ok(Keymap::arrows()),
+            "wasd" => Ok(Keymap::wasd()),
+            _ => Err(format!("Unknown preset '{name}', use vim, arrows or wasd")),
+        }
+    }
+
+    /// the default vim-like keybindings.
+    pub fn vim() -> Keymap {
+        use Action::*;
+        use KeymapSection::*;
+
+        let mut keymap = Keymap {
+            global: HashMap::new(),
+            go: HashMap::new(),
+            visual: HashMap::new(),
+        };
+
+        let bindings: [(KeymapSection, Action, &[char]); 33] = [
+            (Global, MoveLeft, &['h']),
+            (Global, MoveDown, &['j']),
+            (Global, MoveUp, &['k']),
+            (Global, MoveRight, &['l']),
+            (Global, MoveFarLeft, &['H']),
+            (Global, MoveFarDown, &['J']),
+            (Global, MoveFarUp, &['K']),
+            (Global, MoveFarRight, &['L']),
+            (Global, EditMode, &['i']),
+            (Global, EditModeOnce, &['I']),
+            (Global, MarkupMode, &['a']),
+            (Global, MarkupModeOnce, &['A']),
+            (Global, GoMode, &['g', 'G']),
+            (Global, VisualMode, &['v', 'V']),
+            (Global, Toggle, &[' ']),
+            (Global, Delete, &['x']),
+            (Global, Hint, &['?']),
+            (Global, RepeatChange, &['.']),
+            (Global, FillCandidates, &['f']),
+            (Global, FillCurrentCandidates, &['F']),
+            (Global, ToggleConflicts, &['c', 'C']),
+            (Global, ToggleMistakes, &['e', 'E']),
+            (Global, Undo, &['u', 'U']),
+            (Global, Redo, &['r', 'R']),
+            (Global, Export, &['w', 'W']),
+            (Global, SetCheckpoint, &['m']),
+            (Global, JumpToCheckpoint, &['\'']),
+            (Global, CommandLine, &[':']),
+            (Global, Quit, &['q', 'Q']),
+            (Go, UndoChronologically, &['-']),
+            (Go, RedoChronologically, &['+']),
+            (Go, ListBranches, &['b']),
+            (Visual, StampSelection, &['s', 'S']),
+        ];
+
+        for (section, action, keys) in bindings {
+            let keys = keys.iter().map(|&c| Key::Char(c)).collect::<Vec<_>>();
+            keymap.bind(section, action, &keys);
+        }
+        keymap.bind(Visual, PaintSelection, &[Key::Char('c'), Key::Char('C')]);
+        keymap.bind(Global, EditMode, &[Key::Char('i'), Key::Esc]);
+
+        keymap
+    }
+
+    /// the vim-like keybindings, additionally moving with the arrow keys,
+    /// and 3 cells at once with `Home`, `End`, `PageUp` and `PageDown`.
+    pub fn arrows() -> Keymap {
+        use Action::*;
+        use KeymapSection::*;
+
+        let mut keymap = Keymap::vim();
+        keymap.bind(Global, MoveLeft, &[Key::Char('h'), Key::Left]);
+        keymap.bind(Global, MoveDown, &[Key::Char('j'), Key::Down]);
+        keymap.bind(Global, MoveUp, &[Key::Char('k'), Key::Up]);
+        keymap.bind(Global, MoveRight, &[Key::Char('l'), Key::Right]);
+        keymap.bind(Global, MoveFarLeft, &[Key::Char('H'), Key::Home]);
+        keymap.bind(Global, MoveFarDown, &[Key::Char('J'), Key::PageDown]);
+        keymap.bind(Global, MoveFarUp, &[Key::Char('K'), Key::PageUp]);
+        keymap.bind(Global, MoveFarRight, &[Key::Char('L'), Key::End]);
+        keymap
+    }
+
+    /// the arrow key bindings, additionally moving with `w, a, s, d`
+    /// and 3 cells at once with `W, A, S, D`.
+    /// Markup mode moves to `n` and `N`, exporting to `o`
+    /// and adding to the selection in Visual mode to `t`.
+    pub fn wasd() -> Keymap {
+        use Action::*;
+        use KeymapSection::*;
+
+        let mut keymap = Keymap::arrows();
+        let moves = [
+            (MoveLeft, 'a'),
+            (MoveDown, 's'),
+            (MoveUp, 'w'),
+            (MoveRight, 'd'),
+            (MoveFarLeft, 'A'),
+            (MoveFarDown, 'S'),
+            (MoveFarUp, 'W'),
+            (MoveFarRight, 'D'),
+        ];
+        for (action, c) in moves {
+            let mut keys = keymap.keys(Global, action);
+            keys.push(Key::Char(c));
+            keymap.bind(Global, action, &keys);
+        }
+        keymap.bind(Global, MarkupMode, &[Key::Char('n')]);
+        keymap.bind(Global, MarkupModeOnce, &[Key::Char('N')]);
+        keymap.bind(Global, Export, &[Key::Char('o'), Key::Char('O')]);
+        keymap.bind(Visual, StampSelection, &[Key::Char('t'), Key::Char('T')]);
+        keymap
+    }
+
+    fn section(&mut self, section: KeymapSection) -> &mut HashMap<Key, Action> {
+        match section {
+            KeymapSection::Global => &mut self.global,
+            KeymapSection::Go => &mut self.go,
+            KeymapSection::Visual => &mut self.visual,
+        }
+    }
+
+    /// returns the keys bound to `action` in `section`.
+    fn keys(&mut self, section: KeymapSection, action: Action) -> Vec<Key> {
+        self.section(section)
+            .iter()
+            .filter(|&(_, &bound)| bound == action)
+            .map(|(&key, _)| key)
+            .collect()
+    }
+
+    /// binds `keys` to `action` in `section`, replacing the keys
+    /// previously bound to `action` and the previous actions of `keys` there.
+    pub fn bind(&mut self, section: KeymapSection, action: Action, keys: &[Key]) {
+        let bindings = self.section(section);
+        bindings.retain(|_, &mut bound| bound != action);
+        for &key in keys {
+            bindings.insert(key, action);
+        }
+    }
+
+    /// returns the action bound to `key` in `mode`.
+    pub fn action(&self, key: Key, mode: Mode) -> Option<Action> {
+        let section = match mode {
+            Mode::Go => self.go.get(&key),
+            Mode::Visual => self.visual.get(&key),
+            _ => None,
+        };
+        section.or(self.global.get(&key)).copied()
+    }
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        Keymap::vim()
+    }
+}
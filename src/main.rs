@@ -1,105 +1,531 @@
 extern crate crossterm;
-use crossterm::event::{poll, read, Event::*, KeyCode::*};
+use crossterm::event::{poll, read, Event, KeyCode::*};
 
 extern crate rand;
+extern crate rand_chacha;
 
 mod cli;
+mod commands;
+mod config;
+mod ex;
+mod keys;
+mod menu;
+mod paths;
+mod save;
+mod scores;
 mod state;
 mod sudoku;
+mod theme;
 mod ui;
-use {state::*, sudoku::*, ui::*};
+mod undo;
+use {
+    keys::*,
+    menu::{Menu, MenuChoice},
+    state::*,
+    sudoku::*,
+    theme::*,
+    ui::*,
+};
 
-use std::{io, time::Duration};
+use std::{fs, io, path::Path, time::Duration};
 
 fn main() {
     let args = cli::new().get_matches();
-    let difficulty = match args.get_one::<String>("difficulty") {
-        None => Difficulty::Mid,
-        Some(d) => d.parse().unwrap(),
+
+    match args.subcommand() {
+        Some(("solve", solve_args)) => std::process::exit(commands::solve(solve_args)),
+        Some(("generate", generate_args)) => std::process::exit(commands::generate(generate_args)),
+        Some(("validate", validate_args)) => std::process::exit(commands::validate(validate_args)),
+        Some(("scores", scores_args)) => std::process::exit(commands::scores(scores_args)),
+        _ => {}
+    }
+
+    let config = match config::load() {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("[!]: Error: {e}");
+            std::process::exit(1);
+        }
     };
+    let keymap = config.keymap;
 
-    let mut screen = Ui::init(io::stdout());
-    let mut state = State::init(difficulty);
-    screen.draw_static_elements().or_crash();
+    let save_path = save::default_path();
+    let mut resume = args.get_flag("resume");
+    let graded = args.get_flag("graded");
+    let seed = args.get_one::<u64>("seed").copied();
 
-    loop {
-        if poll(Duration::from_millis(250)).unwrap_or(false) {
-            if let Ok(Key(k)) = read() {
-                match k.code {
-                    Char('h') => state.move_cursor(Dir::Left),
-                    Char('j') => state.move_cursor(Dir::Down),
-                    Char('k') => state.move_cursor(Dir::Up),
-                    Char('l') => state.move_cursor(Dir::Right),
+    let state = match args.get_one::<Board>("puzzle") {
+        _ if resume => {
+            let loaded = match &save_path {
+                Some(path) if path.exists() => save::read(path),
+                _ => Err(String::from("No saved game found.")),
+            };
+            match loaded {
+                Ok(state) => Some(state),
+                Err(e) => {
+                    eprintln!("[!]: Error: {e}");
+                    std::process::exit(1);
+                }
+            }
+        }
+        Some(&board) => Some(State::from_puzzle(board)),
+        None => args.get_one::<String>("difficulty").map(|d| {
+            let seed = seed.unwrap_or_else(random_seed);
+            State::new_game(d.parse().unwrap(), seed, graded)
+        }),
+    };
 
-                    Char('H') => state.move_cursor(Dir::FarLeft),
-                    Char('J') => state.move_cursor(Dir::FarDown),
-                    Char('K') => state.move_cursor(Dir::FarUp),
-                    Char('L') => state.move_cursor(Dir::FarRight),
+    let mut screen = Ui::init(io::stdout(), config.theme);
 
-                    Char('i') => state.enter_mode(Mode::Edit),
-                    Char('I') => state.enter_mode_once(Mode::Edit),
+    // without a game given on the command line, the player chooses one in the menu
+    let mut state = match state {
+        Some(state) => state,
+        None => match start_menu(&mut screen, &keymap, save_path.as_deref(), seed, graded) {
+            Some((state, resumed)) => {
+                resume = resumed;
+                state
+            }
+            None => {
+                screen.deinit().or_crash();
+                return;
+            }
+        },
+    };
 
-                    Char('a') => state.enter_mode(Mode::Markup),
-                    Char('A') => state.enter_mode_once(Mode::Markup),
+    if args.get_flag("strikes") && !resume {
+        state.max_mistakes = Some(3);
+    }
+    if args.get_flag("check") || state.max_mistakes.is_some() {
+        state.check_mistakes = state.solution.is_some();
+    }
 
-                    Char('g') | Char('G') => state.enter_mode_once(Mode::Go),
+    let output = args.get_one::<String>("output");
+    let mut export = None;
+    let mut pending_action = None;
+    let mut count = Count::default();
 
-                    Char(' ') => match state.mode {
-                        Mode::Markup => {
-                            state.toggle_current_mark();
-                            state.enter_next_mode();
+    screen.clear().or_crash();
+    screen.draw_static_elements().or_crash();
+
+    loop {
+        if poll(Duration::from_millis(250)).unwrap_or(false) {
+            if let Ok(Event::Key(k)) = read() {
+                let action = Key::from_code(k.code).and_then(|key| keymap.action(key, state.mode));
+                let times = match (k.code, action) {
+                    (Char('0'..='9'), _)
+                        if state.mode != Mode::Go
+                            && pending_action.is_none()
+                            && state.command_line.is_none() =>
+                    {
+                        1
+                    }
+                    (_, Some(Action::GoMode)) => 1,
+                    _ => {
+                        let (times, preselection) = count.take(action);
+                        if let Some(num) = preselection {
+                            state.preselect_num(num);
                         }
-                        Mode::Edit => {
-                            state.toggle_current_cell();
-                            state.enter_next_mode();
-                            if is_solution(&state.board) {
-                                screen.deinit().or_crash();
-                                println!("+------------+");
-                                println!("| You Win :) |");
-                                println!("+------------+");
-                                println!("Difficulty: {}", state.difficulty);
-                                println!("Final Time: {}", state.get_timer_string());
-                                break;
+                        times
+                    }
+                };
+
+                match k.code {
+                    Char(c) if state.command_line.is_some() => {
+                        state.command_line.get_or_insert_with(String::new).push(c)
+                    }
+                    Backspace if state.command_line.is_some() => {
+                        if let Some(line) = &mut state.command_line {
+                            if line.pop().is_none() {
+                                state.command_line = None;
                             }
                         }
-                        _ => {}
-                    },
-
-                    Char('x') => {
-                        if state.current_cell_is_modifiable() {
-                            match state.mode {
-                                Mode::Go => {}
-                                Mode::Edit => state.delete_current_cell(),
-                                Mode::Markup => state.delete_current_mark(),
+                    }
+                    Enter if state.command_line.is_some() => {
+                        let line = state.command_line.take().unwrap_or_default();
+                        match ex::parse(&line) {
+                            Ok(ex::ExCommand::Quit { save }) => {
+                                if save && asks_to_overwrite(&mut state, save_path.as_deref()) {
+                                    pending_action = Some(Action::Quit);
+                                } else {
+                                    quit(&mut screen, &state, save_path.as_deref(), save);
+                                    break;
+                                }
+                            }
+                            Ok(ex::ExCommand::Theme(name)) => {
+                                state.message = match name {
+                                    None => pick_theme(&mut screen, &keymap),
+                                    Some(name) => match Theme::by_name(&name) {
+                                        Ok(theme) => {
+                                            screen.theme = theme;
+                                            screen.clear().or_crash();
+                                            save_theme(&theme)
+                                        }
+                                        Err(e) => e,
+                                    },
+                                };
+                                screen.draw_static_elements().or_crash();
                             }
+                            Ok(command) => {
+                                if let Err(e) = ex::run(command, &mut state, graded) {
+                                    state.message = e;
+                                }
+                            }
+                            Err(e) => state.message = e,
                         }
                     }
+                    Esc if state.command_line.is_some() => state.command_line = None,
+                    _ if state.command_line.is_some() => {}
 
+                    Char(name) if pending_action == Some(Action::SetCheckpoint) => {
+                        pending_action = None;
+                        state.set_checkpoint(name);
+                    }
+                    Char(name) if pending_action == Some(Action::JumpToCheckpoint) => {
+                        pending_action = None;
+                        state.jump_to_checkpoint(name);
+                    }
+                    Char(answer @ ('y' | 'n')) if pending_action == Some(Action::Quit) => {
+                        quit(&mut screen, &state, save_path.as_deref(), answer == 'y');
+                        break;
+                    }
+                    _ if pending_action == Some(Action::Quit) => {
+                        pending_action = None;
+                        state.message = String::from("Not quitting");
+                    }
+                    _ if pending_action.is_some() => pending_action = None,
+
+                    // a bound `0` takes precedence over starting a count
+                    Char('0')
+                        if state.mode != Mode::Go && (action.is_none() || !count.is_empty()) =>
+                    {
+                        count.push(0, state.preselection)
+                    }
                     Char(num) if ('1'..='9').contains(&num) => match state.mode {
                         Mode::Go => {
                             let idx = (num as u8 - b'1') as usize;
                             state.move_cursor_to(1 + idx / 3 * 3, 1 + idx % 3 * 3);
                             state.enter_next_mode();
                         }
-                        _ => state.preselect_num(num as u8 - b'0'),
+                        _ => {
+                            let digit = num as u8 - b'0';
+                            count.push(digit, state.preselection);
+                            state.preselect_num(digit);
+                        }
                     },
 
-                    Char('u') | Char('U') => state.undo(),
-                    Char('r') | Char('R') => state.redo(),
+                    _ => match action {
+                        Some(Action::MoveLeft) => repeat(times, || state.move_cursor(Dir::Left)),
+                        Some(Action::MoveDown) => repeat(times, || state.move_cursor(Dir::Down)),
+                        Some(Action::MoveUp) => repeat(times, || state.move_cursor(Dir::Up)),
+                        Some(Action::MoveRight) => repeat(times, || state.move_cursor(Dir::Right)),
 
-                    Char('q') | Char('Q') => {
-                        screen.deinit().or_crash();
-                        break;
-                    }
+                        Some(Action::MoveFarLeft) => {
+                            repeat(times, || state.move_cursor(Dir::FarLeft))
+                        }
+                        Some(Action::MoveFarDown) => {
+                            repeat(times, || state.move_cursor(Dir::FarDown))
+                        }
+                        Some(Action::MoveFarUp) => repeat(times, || state.move_cursor(Dir::FarUp)),
+                        Some(Action::MoveFarRight) => {
+                            repeat(times, || state.move_cursor(Dir::FarRight))
+                        }
+
+                        Some(Action::EditMode) => state.enter_mode(Mode::Edit),
+                        Some(Action::EditModeOnce) => state.enter_mode_once(Mode::Edit),
+
+                        Some(Action::MarkupMode) => state.enter_mode(Mode::Markup),
+                        Some(Action::MarkupModeOnce) => state.enter_mode_once(Mode::Markup),
+
+                        Some(Action::GoMode) => state.enter_mode_once(Mode::Go),
+
+                        Some(Action::VisualMode) => match state.mode {
+                            Mode::Visual => state.enter_next_mode(),
+                            _ => state.enter_mode(Mode::Visual),
+                        },
+                        Some(Action::StampSelection) if state.mode == Mode::Visual => {
+                            state.stamp_selection()
+                        }
+                        Some(Action::PaintSelection) if state.mode == Mode::Visual => {
+                            state.paint_selection()
+                        }
+
+                        Some(Action::Toggle) => match state.mode {
+                            Mode::Markup | Mode::Edit => {
+                                state.toggle_cells(times);
+                                state.enter_next_mode();
+                            }
+                            Mode::Visual => state.mark_selection(),
+                            Mode::Go => {}
+                        },
+
+                        Some(Action::Delete) if state.mode == Mode::Visual => {
+                            state.clear_selection_marks()
+                        }
+                        Some(Action::Delete) => {
+                            if state.current_cell_is_modifiable() {
+                                match state.mode {
+                                    Mode::Go | Mode::Visual => {}
+                                    Mode::Edit => state.delete_current_cell(),
+                                    Mode::Markup => state.delete_current_mark(),
+                                }
+                            }
+                        }
+
+                        Some(Action::UndoChronologically) => {
+                            repeat(times, || state.undo_chronologically());
+                            state.enter_next_mode();
+                        }
+                        Some(Action::RedoChronologically) => {
+                            repeat(times, || state.redo_chronologically());
+                            state.enter_next_mode();
+                        }
+                        Some(Action::ListBranches) => {
+                            state.list_branches();
+                            state.enter_next_mode();
+                        }
 
-                    Esc => state.enter_mode(Mode::Edit),
-                    _ => {}
+                        Some(action @ (Action::SetCheckpoint | Action::JumpToCheckpoint)) => {
+                            pending_action = Some(action)
+                        }
+
+                        Some(Action::Hint) => repeat(times, || state.hint()),
+                        Some(Action::RepeatChange) => state.repeat_last_change(),
+                        Some(Action::FillCandidates) => state.fill_all_candidates(),
+                        Some(Action::FillCurrentCandidates) => state.fill_current_candidates(),
+                        Some(Action::ToggleConflicts) => state.toggle_conflict_highlighting(),
+                        Some(Action::ToggleMistakes) => state.toggle_mistake_checking(),
+
+                        Some(Action::Undo) => repeat(times, || state.undo()),
+                        Some(Action::Redo) => repeat(times, || state.redo()),
+
+                        Some(Action::Export) => match output {
+                            Some(path) => match fs::write(path, state.export()) {
+                                Ok(_) => state.message = format!("Exported to {path}"),
+                                Err(e) => state.message = format!("Failed to export: {e}"),
+                            },
+                            None => {
+                                export = Some(state.export());
+                                state.message = String::from("Board will be printed when quitting");
+                            }
+                        },
+
+                        Some(Action::CommandLine) => state.command_line = Some(String::new()),
+
+                        Some(Action::Quit) => {
+                            if asks_to_overwrite(&mut state, save_path.as_deref()) {
+                                pending_action = Some(Action::Quit);
+                            } else {
+                                quit(&mut screen, &state, save_path.as_deref(), true);
+                                break;
+                            }
+                        }
+
+                        Some(Action::StampSelection | Action::PaintSelection) | None => {}
+                    },
+                }
+
+                if is_solution(&state.board) {
+                    screen.deinit().or_crash();
+                    if let Some(path) = save_path.as_deref() {
+                        if save::is_saved(&state, path) {
+                            fs::remove_file(path).unwrap_or(());
+                        }
+                    }
+                    println!("+------------+");
+                    println!("| You Win :) |");
+                    println!("+------------+");
+                    println!("Difficulty: {}", state.difficulty);
+                    if let Some(command) = state.replay_command() {
+                        println!("Replay:     {command}");
+                    }
+                    println!("Final Time: {}", state.get_timer_string());
+                    println!("Hints Used: {}", state.hints_used);
+                    if state.check_mistakes {
+                        println!("Mistakes:   {}", state.mistakes);
+                    }
+                    let recorded = match (scores::Score::of(&state), scores::default_path()) {
+                        (None, _) => Ok(None),
+                        (Some(score), Some(path)) => scores::record(score, &path),
+                        (Some(_), None) => {
+                            Err(String::from("No data directory to save the score in."))
+                        }
+                    };
+                    match recorded {
+                        Ok(Some(rank)) => println!("Highscore:  #{rank} of {}", state.difficulty),
+                        Ok(None) => {}
+                        Err(e) => eprintln!("[!]: Error: {e}"),
+                    }
+                    break;
+                }
+                if state.is_game_over() {
+                    screen.deinit().or_crash();
+                    if let Some(path) = save_path.as_deref() {
+                        if save::is_saved(&state, path) {
+                            fs::remove_file(path).unwrap_or(());
+                        }
+                    }
+                    println!("+--------------+");
+                    println!("| Game Over :( |");
+                    println!("+--------------+");
+                    println!("Difficulty: {}", state.difficulty);
+                    if let Some(command) = state.replay_command() {
+                        println!("Replay:     {command}");
+                    }
+                    println!("Time:       {}", state.get_timer_string());
+                    println!("Mistakes:   {}", state.mistakes);
+                    break;
                 }
             }
         }
 
         screen.draw(&state).or_crash();
     }
+
+    if let Some(export) = export {
+        print!("{export}");
+    }
+}
+
+/// resets the terminal for quitting, saving the game to `save_path` if `save` is set.
+fn quit<T: io::Write>(screen: &mut Ui<T>, state: &State, save_path: Option<&Path>, save: bool) {
+    screen.deinit().or_crash();
+    if !save {
+        return;
+    }
+    match save_path {
+        Some(path) => match save::write(state, path) {
+            Ok(_) => println!("Game saved, continue with `shdoku --resume`."),
+            Err(e) => eprintln!("[!]: Error: {e}"),
+        },
+        None => eprintln!("[!]: Error: No data directory to save the game in."),
+    }
+}
+
+/// returns whether quitting would overwrite the save of another game,
+/// asking the player to confirm it in `state.message` if so.
+fn asks_to_overwrite(state: &mut State, save_path: Option<&Path>) -> bool {
+    let overwrites = save_path.is_some_and(|path| path.exists() && !save::is_saved(state, path));
+    if overwrites {
+        state.message = String::from("Overwrite the other saved game? y: save and quit, n: quit");
+    }
+    overwrites
+}
+
+/// shows the start menu until a game is chosen,
+/// returning it and whether it is the one saved at `save_path`.
+/// new games are generated from `seed` if given.
+/// returns `None` if the player quits.
+fn start_menu<T: io::Write>(
+    screen: &mut Ui<T>,
+    keymap: &Keymap,
+    save_path: Option<&Path>,
+    seed: Option<u64>,
+    graded: bool,
+) -> Option<(State, bool)> {
+    let (scores, error) = match scores::default_path().map(|path| scores::read(&path)) {
+        Some(Ok(scores)) => (scores, None),
+        Some(Err(e)) => (Vec::new(), Some(e)),
+        None => (Vec::new(), None),
+    };
+    let mut menu = Menu::new(save_path.is_some_and(Path::exists), scores);
+    menu.message = error.unwrap_or_default();
+
+    loop {
+        screen.draw_menu(&menu).or_crash();
+
+        if !poll(Duration::from_millis(250)).unwrap_or(false) {
+            continue;
+        }
+        let Ok(Event::Key(k)) = read() else {
+            continue;
+        };
+        let Some(key) = Key::from_code(k.code) else {
+            continue;
+        };
+
+        let showing_scores = menu.scores_page.is_some();
+        let choice = menu.handle(key, keymap.action(key, Mode::Edit));
+        if menu.scores_page.is_some() != showing_scores {
+            screen.clear().or_crash();
+        }
+
+        let loaded = match choice {
+            None => continue,
+            Some(MenuChoice::New(difficulty)) => {
+                let seed = seed.unwrap_or_else(random_seed);
+                return Some((State::new_game(difficulty, seed, graded), false));
+            }
+            Some(MenuChoice::Resume) => match save_path {
+                Some(path) => save::read(path).map(|state| (state, true)),
+                None => Err(String::from("No saved game found.")),
+            },
+            Some(MenuChoice::Load(file)) => ex::open(&file).map(|state| (state, false)),
+            Some(MenuChoice::Theme) => {
+                menu.message = pick_theme(screen, keymap);
+                continue;
+            }
+            Some(MenuChoice::Quit) => return None,
+        };
+        match loaded {
+            Ok(game) => return Some(game),
+            Err(e) => menu.message = e,
+        }
+    }
+}
+
+/// shows the theme picker until a theme is chosen, previewing the selected one.
+/// keeps the previous theme if choosing is canceled.
+/// returns a message telling whether the chosen theme was saved.
+///
+/// NOTE: this clears the screen when done.
+fn pick_theme<T: io::Write>(screen: &mut Ui<T>, keymap: &Keymap) -> String {
+    let previous = screen.theme;
+    let mut picker = ThemePicker::new(&previous);
+    screen.clear().or_crash();
+
+    loop {
+        screen.draw_theme_picker(&picker).or_crash();
+
+        if !poll(Duration::from_millis(250)).unwrap_or(false) {
+            continue;
+        }
+        let Ok(Event::Key(k)) = read() else {
+            continue;
+        };
+        let Some(key) = Key::from_code(k.code) else {
+            continue;
+        };
+
+        match picker.handle(key, keymap.action(key, Mode::Edit)) {
+            None => {}
+            Some(true) => break,
+            Some(false) => {
+                screen.theme = previous;
+                break;
+            }
+        }
+    }
+
+    screen.clear().or_crash();
+    match screen.theme == previous {
+        true => String::new(),
+        false => save_theme(&screen.theme),
+    }
+}
+
+/// saves `theme` in the config file, returning a message telling whether it was.
+fn save_theme(theme: &Theme) -> String {
+    match config::save_theme(theme) {
+        Ok(_) => format!("Theme {} saved", theme.name),
+        Err(e) => e,
+    }
+}
+
+/// calls `action` `times` times.
+fn repeat(times: usize, mut action: impl FnMut()) {
+    for _ in 0..times {
+        action();
+    }
 }
 
 mod tests;
@@ -0,0 +1,184 @@
+use crate::keys::*;
+use crate::scores::{self, Score};
+use crate::sudoku::Difficulty;
+
+/// an entry of the start menu.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum MenuItem {
+    /// a new game of a named difficulty
+    New(Difficulty),
+    /// a new game with the number of blank cells chosen in the menu
+    Custom,
+    Resume,
+    /// asks for a file with a saved game or puzzles to play
+    Load,
+    /// shows the highscores
+    Scores,
+    /// asks for the theme to draw the ui in
+    Theme,
+    Quit,
+}
+
+/// what was chosen in the start menu.
+#[derive(Debug, PartialEq)]
+pub enum MenuChoice {
+    New(Difficulty),
+    Resume,
+    Load(String),
+    Theme,
+    Quit,
+}
+
+/// state of the start menu shown when no game was given on the command line.
+#[derive(Debug, PartialEq)]
+pub struct Menu {
+    pub items: Vec<MenuItem>,
+    pub selected: usize,
+    /// the number of blank cells of a custom game
+    pub custom_count: usize,
+    /// the file name being typed after choosing `MenuItem::Load`
+    pub input: Option<String>,
+    pub message: String,
+    pub scores: Vec<Score>,
+    /// the difficulty whose highscores are shown instead of the menu,
+    /// as an index into `scores::difficulties()`
+    pub scores_page: Option<usize>,
+}
+
+impl Menu {
+    /// returns the menu with `Mid` selected,
+    /// offering to resume a game only if `can_resume` is set.
+    pub fn new(can_resume: bool, scores: Vec<Score>) -> Self {
+        let mut items = vec![
+            MenuItem::New(Difficulty::Easy),
+            MenuItem::New(Difficulty::Mid),
+            MenuItem::New(Difficulty::Hard),
+            MenuItem::New(Difficulty::Expert),
+            MenuItem::Custom,
+        ];
+        if can_resume {
+            items.push(MenuItem::Resume);
+        }
+        items.push(MenuItem::Load);
+        items.push(MenuItem::Scores);
+        items.push(MenuItem::Theme);
+        items.push(MenuItem::Quit);
+
+        Menu {
+            items,
+            selected: 1,
+            custom_count: Difficulty::Mid.removal_count(),
+            input: None,
+            message: String::new(),
+            scores,
+            scores_page: None,
+        }
+    }
+
+    /// returns the selected entry.
+    pub fn current(&self) -> MenuItem {
+        self.items[self.selected]
+    }
+
+    /// handles a key press, with `action` being the action bound to `key`.
+    /// returns the choice if one was made.
+    ///
+    /// NOTE: up and down move through the entries, left and right change the
+    ///       custom count, in addition to the keys bound to moving the cursor.
+    pub fn handle(&mut self, key: Key, action: Option<Action>) -> Option<MenuChoice> {
+        if let Some(input) = &mut self.input {
+            match key {
+                Key::Char(c) => input.push(c),
+                Key::Backspace if input.pop().is_none() => self.input = None,
+                Key::Enter => {
+                    let file = self.input.take().unwrap_or_default();
+                    return match file.trim() {
+                        "" => None,
+                        file => Some(MenuChoice::Load(file.to_string())),
+                    };
+                }
+                Key::Esc => self.input = None,
+                _ => {}
+            }
+            return None;
+        }
+
+        if let Some(page) = self.scores_page {
+            let pages = scores::difficulties(&self.scores).len();
+            self.scores_page = match (key, action) {
+                (Key::Left, _) | (_, Some(Action::MoveLeft)) => Some((page + pages - 1) % pages),
+                (Key::Right, _) | (_, Some(Action::MoveRight)) => Some((page + 1) % pages),
+                _ => None,
+            };
+            return None;
+        }
+
+        self.message.clear();
+        let len = self.items.len();
+
+        match (key, action) {
+            (Key::Up, _) | (_, Some(Action::MoveUp)) => {
+                self.selected = (self.selected + len - 1) % len
+            }
+            (Key::Down, _) | (_, Some(Action::MoveDown)) => {
+                self.selected = (self.selected + 1) % len
+            }
+            (_, Some(Action::MoveFarUp)) => self.selected = 0,
+            (_, Some(Action::MoveFarDown)) => self.selected = len - 1,
+
+            (Key::Left, _) | (_, Some(Action::MoveLeft)) => self.change_custom_count(-1),
+            (Key::Right, _) | (_, Some(Action::MoveRight)) => self.change_custom_count(1),
+            (_, Some(Action::MoveFarLeft)) => self.change_custom_count(-10),
+            (_, Some(Action::MoveFarRight)) => self.change_custom_count(10),
+            (Key::Char(digit @ '0'..='9'), _) => {
+                self.selected = self
+                    .items
+                    .iter()
+                    .position(|&item| item == MenuItem::Custom)?;
+                let count = self.custom_count * 10 + (digit as u8 - b'0') as usize;
+                self.custom_count = match count {
+                    0..=81 => count,
+                    _ => (digit as u8 - b'0') as usize,
+                };
+            }
+
+            (Key::Enter, _) | (_, Some(Action::Toggle)) => {
+                return match self.current() {
+                    MenuItem::New(difficulty) => Some(MenuChoice::New(difficulty)),
+                    MenuItem::Custom => {
+                        Some(MenuChoice::New(Difficulty::Custom(self.custom_count)))
+                    }
+                    MenuItem::Resume => Some(MenuChoice::Resume),
+                    MenuItem::Load => {
+                        self.input = Some(String::new());
+                        None
+                    }
+                    MenuItem::Scores => {
+                        self.scores_page = Some(0);
+                        None
+                    }
+                    MenuItem::Theme => Some(MenuChoice::Theme),
+                    MenuItem::Quit => Some(MenuChoice::Quit),
+                };
+            }
+            (Key::Esc, _) | (_, Some(Action::Quit)) => return Some(MenuChoice::Quit),
+            _ => {}
+        }
+        None
+    }
+
+    /// returns the difficulty whose highscores are shown, if they are.
+    pub fn scores_difficulty(&self) -> Option<Difficulty> {
+        let page = self.scores_page?;
+        scores::difficulties(&self.scores).get(page).copied()
+    }
+
+    /// changes the custom count by `by`, keeping it between 0 and 81,
+    /// and selects the custom entry.
+    fn change_custom_count(&mut self, by: isize) {
+        if let Some(custom) = self.items.iter().position(|&item| item == MenuItem::Custom) {
+            self.selected = custom;
+            self.custom_count = (self.custom_count as isize + by).clamp(0, 81) as usize;
+        }
+    }
+}
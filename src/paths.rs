@@ -0,0 +1,33 @@
+use std::{env, path::PathBuf};
+
+/// returns the directory shdoku keeps its data in, such as saved games.
+/// this is `$XDG_DATA_HOME/shdoku` or `~/.local/share/shdoku`,
+/// and `%APPDATA%\shdoku` on windows.
+pub fn data_dir() -> Option<PathBuf> {
+    #[cfg(windows)]
+    let base = env::var_os("APPDATA").map(PathBuf::from);
+
+    #[cfg(not(windows))]
+    let base = env::var_os("XDG_DATA_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".local/share")));
+
+    base.map(|dir| dir.join("shdoku"))
+}
+
+/// returns the directory shdoku reads its configuration from.
+/// this is `$XDG_CONFIG_HOME/shdoku` or `~/.config/shdoku`,
+/// and `%APPDATA%\shdoku` on windows.
+pub fn config_dir() -> Option<PathBuf> {
+    #[cfg(windows)]
+    let base = env::var_os("APPDATA").map(PathBuf::from);
+
+    #[cfg(not(windows))]
+    let base = env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")));
+
+    base.map(|dir| dir.join("shdoku"))
+}
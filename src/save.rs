@@ -0,0 +1,295 @@
+use crate::paths;
+use crate::state::*;
+use crate::sudoku::*;
+use crate::undo::UndoTree;
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+/// the version of the save file format.
+/// increase this whenever old save files can no longer be read.
+const VERSION: u32 = 2;
+
+/// returns the path of the saved game in the data directory.
+pub fn default_path() -> Option<PathBuf> {
+    paths::data_dir().map(|dir| dir.join("save"))
+}
+
+/// writes the game to `path`, creating missing directories.
+pub fn write(state: &State, path: &Path) -> Result<(), String> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|e| format!("Failed to create {}: {e}", dir.display()))?;
+    }
+    fs::write(path, serialize(state))
+        .map_err(|e| format!("Failed to write {}: {e}", path.display()))
+}
+
+/// reads a game saved with `write()` from `path`.
+pub fn read(path: &Path) -> Result<State, String> {
+    let text =
+        fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {e}", path.display()))?;
+    deserialize(&text)
+}
+
+/// returns whether the game saved at `path` is one of the same puzzle as `state`.
+pub fn is_saved(state: &State, path: &Path) -> bool {
+    read(path).is_ok_and(|saved| saved.get_puzzle() == state.get_puzzle())
+}
+
+/// returns the game as text, one `key value` entry per line,
+/// starting with a header containing the format version.
+///
+/// the board is stored in the 81-character line format, the modifiable
+/// mask and marks as 81 comma separated cells, and each node of the undo tree
+/// on its own line as `change parent redo_child step`. (see `write_step()`)
+pub fn serialize(state: &State) -> String {
+    let mut lines = vec![format!("shdoku-save {VERSION}")];
+
+    let difficulty = match state.difficulty {
+        Difficulty::Custom(x) => x.to_string(),
+        d => d.to_string().to_lowercase(),
+    };
+    lines.push(format!("difficulty {difficulty}"));
+    if let Some(seed) = state.seed {
+        lines.push(format!("seed {seed}"));
+    }
+    lines.push(format!("graded {}", state.graded as u8));
+    lines.push(format!("elapsed {}", state.get_elapsed_time().as_millis()));
+    lines.push(format!("hints {}", state.hints_used));
+    lines.push(format!("conflicts {}", state.highlight_conflicts as u8));
+    lines.push(format!("check {}", state.check_mistakes as u8));
+    lines.push(format!("mistakes {}", state.mistakes));
+    if let Some(max) = state.max_mistakes {
+        lines.push(format!("max-mistakes {max}"));
+    }
+    lines.push(format!("cursor {} {}", state.cur_row, state.cur_col));
+    lines.push(format!("preselection {}", state.preselection));
+
+    lines.push(format!("board {}", format_line(&state.board)));
+
+    let modifiable = state.modifiable.iter().flatten().map(|&m| match m {
+        true => "1",
+        false => "0",
+    });
+    lines.push(format!(
+        "modifiable {}",
+        modifiable.collect::<Vec<_>>().join(",")
+    ));
+
+    let marks = state.markups.iter().flatten().map(|cell| {
+        (1..=9)
+            .filter(|&x| cell[x - 1])
+            .map(|x| x.to_string())
+            .collect::<String>()
+    });
+    lines.push(format!("marks {}", marks.collect::<Vec<_>>().join(",")));
+
+    let colors = state.colors.iter().flatten().map(|color| color.to_string());
+    lines.push(format!("colors {}", colors.collect::<Vec<_>>().join(",")));
+
+    for (parent, redo_child, step) in state.undo_tree.nodes() {
+        lines.push(format!("change {parent} {redo_child} {}", write_step(step)));
+    }
+    lines.push(format!("current-change {}", state.undo_tree.current()));
+    for (name, node) in &state.checkpoints {
+        lines.push(format!("checkpoint {name} {node}"));
+    }
+
+    lines.join("\n") + "\n"
+}
+
+/// restores a game from the text returned by `serialize()`.
+pub fn deserialize(text: &str) -> Result<State, String> {
+    let mut lines = text.lines();
+
+    match lines
+        .next()
+        .and_then(|header| header.strip_prefix("shdoku-save "))
+    {
+        Some(version) if version == VERSION.to_string() => {}
+        Some(version) => return Err(format!("Unsupported save file version {version}.")),
+        None => return Err(String::from("Not a shdoku save file.")),
+    }
+
+    let mut state = State::init([[0; 9]; 9], Difficulty::default(), None);
+    let mut changes = Vec::new();
+    let mut current_change = 0;
+
+    for line in lines {
+        let (key, value) = line.split_once(' ').unwrap_or((line, ""));
+        let mut tokens = value.split_whitespace();
+        match key {
+            "difficulty" => state.difficulty = value.parse()?,
+            "seed" => state.seed = Some(parse_num(&mut tokens, u64::MAX)?),
+            "graded" => state.graded = parse_num::<u8>(&mut tokens, 1)? == 1,
+            "elapsed" => {
+                let millis = parse_num(&mut tokens, u64::MAX)?;
+                state.set_elapsed_time(Duration::from_millis(millis))?;
+            }
+            "hints" => state.hints_used = parse_num(&mut tokens, usize::MAX)?,
+            "conflicts" => state.highlight_conflicts = parse_num::<u8>(&mut tokens, 1)? == 1,
+            "check" => state.check_mistakes = parse_num::<u8>(&mut tokens, 1)? == 1,
+            "mistakes" => state.mistakes = parse_num(&mut tokens, usize::MAX)?,
+            "max-mistakes" => state.max_mistakes = Some(parse_num(&mut tokens, usize::MAX)?),
+            "cursor" => {
+                state.cur_row = parse_num(&mut tokens, 8)?;
+                state.cur_col = parse_num(&mut tokens, 8)?;
+            }
+            "preselection" => state.preselection = parse_num(&mut tokens, 9)?.max(1),
+            "board" => state.board = parse_line(value)?,
+            "modifiable" => {
+                for (flag, cell) in state.modifiable.iter_mut().flatten().zip(cells(value)?) {
+                    *flag = match cell {
+                        "0" => false,
+                        "1" => true,
+                        _ => return Err(format!("Invalid modifiable cell '{cell}' in save file.")),
+                    };
+                }
+            }
+            "colors" => {
+                for (color, cell) in state.colors.iter_mut().flatten().zip(cells(value)?) {
+                    *color = parse_num(&mut [cell].into_iter(), PAINT_COLOR_COUNT)?;
+                }
+            }
+            "marks" => {
+                for (marks, cell) in state.markups.iter_mut().flatten().zip(cells(value)?) {
+                    for x in cell.bytes() {
+                        if !(b'1'..=b'9').contains(&x) {
+                            return Err(format!("Invalid marks '{cell}' in save file."));
+                        }
+                        marks[(x - b'1') as usize] = true;
+                    }
+                }
+            }
+            "change" => {
+                let parent = parse_num(&mut tokens, usize::MAX)?;
+                let redo_child = parse_num(&mut tokens, usize::MAX)?;
+                changes.push((parent, redo_child, read_step(&mut tokens)?));
+            }
+            "current-change" => current_change = parse_num(&mut tokens, usize::MAX)?,
+            "checkpoint" => {
+                let name = parse_num(&mut tokens, 'z')?;
+                if !name.is_ascii_alphabetic() {
+                    return Err(format!("Invalid checkpoint name '{name}' in save file."));
+                }
+                state
+                    .checkpoints
+                    .insert(name, parse_num(&mut tokens, usize::MAX)?);
+            }
+            _ => return Err(format!("Unknown save file entry '{key}'.")),
+        }
+    }
+
+    state.solution = State::init_solution(state.get_puzzle());
+    if !changes.is_empty() {
+        state.undo_tree = UndoTree::from_nodes(changes, current_change)?;
+    }
+    if state
+        .checkpoints
+        .values()
+        .any(|&node| node >= state.undo_tree.next_node())
+    {
+        return Err(String::from("Checkpoint of a missing change in save file."));
+    }
+
+    Ok(state)
+}
+
+/// returns a `DiffStep` as space separated tokens:
+/// - `E num row col new_num n` followed by the `n` affected mark positions
+/// - `M num row col mark` with `mark` being 0 or 1
+/// - `P color row col`
+/// - `T n` followed by the `n` toggled marks as `num row col`
+/// - `G n` followed by the `n` grouped steps
+fn write_step(step: &DiffStep) -> String {
+    match step {
+        DiffStep::Edit(num, (r, c), marks, new_num) => {
+            let mut tokens = vec![format!("E {num} {r} {c} {new_num} {}", marks.len())];
+            for (r, c) in marks {
+                tokens.push(format!("{r} {c}"));
+            }
+            tokens.join(" ")
+        }
+        DiffStep::Mark(num, (r, c), mark) => format!("M {num} {r} {c} {}", *mark as u8),
+        DiffStep::Paint(color, (r, c)) => format!("P {color} {r} {c}"),
+        DiffStep::Marks(toggled) => {
+            let mut tokens = vec![format!("T {}", toggled.len())];
+            for (num, (r, c)) in toggled {
+                tokens.push(format!("{num} {r} {c}"));
+            }
+            tokens.join(" ")
+        }
+        DiffStep::Group(steps) => {
+            let mut tokens = vec![format!("G {}", steps.len())];
+            tokens.extend(steps.iter().map(write_step));
+            tokens.join(" ")
+        }
+    }
+}
+
+/// reads a `DiffStep` written by `write_step()`.
+fn read_step<'a>(tokens: &mut impl Iterator<Item = &'a str>) -> Result<DiffStep, String> {
+    match tokens.next() {
+        Some("E") => {
+            let num = parse_num(tokens, 9)?;
+            let pos = (parse_num(tokens, 8)?, parse_num(tokens, 8)?);
+            let new_num = parse_num(tokens, 9)?;
+            let mut marks = Vec::new();
+            for _ in 0..parse_num(tokens, 81 * 3)? {
+                marks.push((parse_num(tokens, 8)?, parse_num(tokens, 8)?));
+            }
+            Ok(DiffStep::Edit(num, pos, marks, new_num))
+        }
+        Some("M") => {
+            let num = parse_num(tokens, 9)?.max(1);
+            let pos = (parse_num(tokens, 8)?, parse_num(tokens, 8)?);
+            Ok(DiffStep::Mark(num, pos, parse_num::<u8>(tokens, 1)? == 1))
+        }
+        Some("P") => {
+            let color = parse_num(tokens, PAINT_COLOR_COUNT)?;
+            let pos = (parse_num(tokens, 8)?, parse_num(tokens, 8)?);
+            Ok(DiffStep::Paint(color, pos))
+        }
+        Some("T") => {
+            let mut toggled = Vec::new();
+            for _ in 0..parse_num(tokens, 81 * 9)? {
+                let num = parse_num(tokens, 9)?.max(1);
+                toggled.push((num, (parse_num(tokens, 8)?, parse_num(tokens, 8)?)));
+            }
+            Ok(DiffStep::Marks(toggled))
+        }
+        Some("G") => {
+            let mut steps = Vec::new();
+            for _ in 0..parse_num(tokens, usize::MAX)? {
+                steps.push(read_step(tokens)?);
+            }
+            Ok(DiffStep::Group(steps))
+        }
+        Some(kind) => Err(format!("Unknown undo step '{kind}'.")),
+        None => Err(String::from("Missing undo step.")),
+    }
+}
+
+/// splits the comma separated cells of a line, which has to contain all 81.
+fn cells(value: &str) -> Result<Vec<&str>, String> {
+    let cells = value.split(',').collect::<Vec<_>>();
+    match cells.len() {
+        81 => Ok(cells),
+        n => Err(format!("Expected 81 cells in save file, found {n}.")),
+    }
+}
+
+/// parses the next token as a number no larger than `max`.
+fn parse_num<'a, T>(tokens: &mut impl Iterator<Item = &'a str>, max: T) -> Result<T, String>
+where
+    T: std::str::FromStr + PartialOrd,
+{
+    let token = tokens.next().ok_or("Missing number in save file.")?;
+    match token.parse() {
+        Ok(num) if num <= max => Ok(num),
+        _ => Err(format!("Invalid number '{token}' in save file.")),
+    }
+}
@@ -0,0 +1,249 @@
+use crate::paths;
+use crate::state::State;
+use crate::sudoku::Difficulty;
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+/// the version of the highscore file format.
+const VERSION: u32 = 1;
+
+/// the number of scores kept per difficulty.
+pub const TOP_COUNT: usize = 10;
+
+/// a won game.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Score {
+    pub difficulty: Difficulty,
+    /// the final time in seconds
+    pub secs: u64,
+    /// the day the game was won, as `yyyy-mm-dd`
+    pub date: String,
+    pub hints: usize,
+    /// the mistakes made, if they were checked
+    pub mistakes: Option<usize>,
+}
+
+impl Score {
+    /// returns the score of the won game in `state`, dated today.
+    ///
+    /// NOTE: puzzles without a cell to fill, like `-d 0`, are won
+    ///       on the first key press and get no score.
+    pub fn of(state: &State) -> Option<Self> {
+        if !state
+            .modifiable
+            .iter()
+            .flatten()
+            .any(|&modifiable| modifiable)
+        {
+            return None;
+        }
+        Some(Score {
+            difficulty: state.difficulty,
+            secs: state.get_elapsed_time().as_secs(),
+            date: today(),
+            hints: state.hints_used,
+            mistakes: state.check_mistakes.then_some(state.mistakes),
+        })
+    }
+
+    /// returns the final time in the `mm:ss` format of the ingame timer.
+    pub fn time_string(&self) -> String {
+        format!("{:02}:{:02}", self.secs / 60, self.secs % 60)
+    }
+
+    /// returns what scores are ranked by:
+    /// the time, then the hints used, then the mistakes.
+    ///
+    /// NOTE: games without checked mistakes rank after the checked ones.
+    fn rank_key(&self) -> (u64, usize, bool, usize) {
+        let mistakes = self.mistakes.unwrap_or(0);
+        (self.secs, self.hints, self.mistakes.is_none(), mistakes)
+    }
+}
+
+/// returns the path of the highscores in the data directory.
+pub fn default_path() -> Option<PathBuf> {
+    paths::data_dir().map(|dir| dir.join("scores"))
+}
+
+/// reads the scores saved at `path`, or none if there is no file yet.
+pub fn read(path: &Path) -> Result<Vec<Score>, String> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let text =
+        fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {e}", path.display()))?;
+    deserialize(&text)
+}
+
+/// adds `score` to the scores saved at `path`, keeping the best
+/// `TOP_COUNT` of each difficulty.
+/// returns the rank of `score` among its difficulty, if it was kept.
+pub fn record(score: Score, path: &Path) -> Result<Option<usize>, String> {
+    let mut scores = read(path)?;
+    let rank = top(&scores, score.difficulty)
+        .iter()
+        .take_while(|other| other.rank_key() <= score.rank_key())
+        .count();
+    if rank >= TOP_COUNT {
+        return Ok(None);
+    }
+
+    scores.push(score);
+    let difficulties = difficulties(&scores);
+    let kept = difficulties
+        .into_iter()
+        .flat_map(|difficulty| top(&scores, difficulty))
+        .cloned()
+        .collect::<Vec<_>>();
+
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|e| format!("Failed to create {}: {e}", dir.display()))?;
+    }
+    fs::write(path, serialize(&kept))
+        .map_err(|e| format!("Failed to write {}: {e}", path.display()))?;
+    Ok(Some(rank + 1))
+}
+
+/// returns the best `TOP_COUNT` scores of `difficulty`, best first.
+/// of equal scores, the earlier one ranks first.
+pub fn top(scores: &[Score], difficulty: Difficulty) -> Vec<&Score> {
+    let mut top = scores
+        .iter()
+        .filter(|score| score.difficulty == difficulty)
+        .collect::<Vec<_>>();
+    // NOTE: `sort_by_key()` is stable, keeping the order of the file for equal scores.
+    top.sort_by_key(|score| score.rank_key());
+    top.truncate(TOP_COUNT);
+    top
+}
+
+/// returns the named difficulties followed by the custom ones with scores.
+pub fn difficulties(scores: &[Score]) -> Vec<Difficulty> {
+    let mut difficulties = vec![
+        Difficulty::Easy,
+        Difficulty::Mid,
+        Difficulty::Hard,
+        Difficulty::Expert,
+    ];
+    for score in scores {
+        if !difficulties.contains(&score.difficulty) {
+            difficulties.push(score.difficulty);
+        }
+    }
+    difficulties.sort();
+    difficulties
+}
+
+/// returns the lines of a table of the best scores of `difficulty`,
+/// starting with a header. every line is 38 characters wide.
+pub fn table(scores: &[Score], difficulty: Difficulty) -> Vec<String> {
+    let mut lines = vec![format!(
+        "{:>2}  {:<5}  {:<10}  {:>5}  {:>8}",
+        "#", "Time", "Date", "Hints", "Mistakes"
+    )];
+    for (rank, score) in top(scores, difficulty).into_iter().enumerate() {
+        let mistakes = match score.mistakes {
+            Some(mistakes) => mistakes.to_string(),
+            None => String::from("-"),
+        };
+        lines.push(format!(
+            "{:>2}  {:<5}  {:<10}  {:>5}  {:>8}",
+            rank + 1,
+            score.time_string(),
+            score.date,
+            score.hints,
+            mistakes
+        ));
+    }
+    lines
+}
+
+/// returns the scores as text, one `score difficulty secs date hints mistakes`
+/// entry per line, starting with a header containing the format version.
+/// mistakes are `-` if they were not checked.
+pub fn serialize(scores: &[Score]) -> String {
+    let mut lines = vec![format!("shdoku-scores {VERSION}")];
+    for score in scores {
+        let difficulty = match score.difficulty {
+            Difficulty::Custom(x) => x.to_string(),
+            d => d.to_string().to_lowercase(),
+        };
+        let mistakes = match score.mistakes {
+            Some(mistakes) => mistakes.to_string(),
+            None => String::from("-"),
+        };
+        lines.push(format!(
+            "score {difficulty} {} {} {} {mistakes}",
+            score.secs, score.date, score.hints
+        ));
+    }
+    lines.join("\n") + "\n"
+}
+
+/// parses scores written by `serialize()`.
+pub fn deserialize(text: &str) -> Result<Vec<Score>, String> {
+    let mut lines = text.lines();
+    match lines
+        .next()
+        .and_then(|header| header.strip_prefix("shdoku-scores "))
+    {
+        Some(version) if version == VERSION.to_string() => {}
+        Some(version) => return Err(format!("Unsupported highscore file version {version}.")),
+        None => return Err(String::from("Not a shdoku highscore file.")),
+    }
+
+    let mut scores = Vec::new();
+    for (n, line) in lines
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+    {
+        let invalid = || format!("Invalid highscore on line {}.", n + 2);
+        let tokens = line.split_whitespace().collect::<Vec<_>>();
+        let ["score", difficulty, secs, date, hints, mistakes] = tokens[..] else {
+            return Err(invalid());
+        };
+        scores.push(Score {
+            difficulty: difficulty.parse().map_err(|_| invalid())?,
+            secs: secs.parse().map_err(|_| invalid())?,
+            date: date.to_string(),
+            hints: hints.parse().map_err(|_| invalid())?,
+            mistakes: match mistakes {
+                "-" => None,
+                mistakes => Some(mistakes.parse().map_err(|_| invalid())?),
+            },
+        });
+    }
+    Ok(scores)
+}
+
+/// returns the current date (utc) as `yyyy-mm-dd`.
+fn today() -> String {
+    let secs = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let (year, month, day) = civil_date((secs / 86400) as i64);
+    format!("{year:04}-{month:02}-{day:02}")
+}
+
+/// returns the (year, month, day) of the gregorian calendar
+/// `days` days after 1970-01-01.
+///
+/// NOTE: this is Howard Hinnant's `civil_from_days()`.
+pub fn civil_date(days: i64) -> (i64, u32, u32) {
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + (month <= 2) as i64;
+    (year, month, day)
+}
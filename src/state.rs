@@ -1,13 +1,20 @@
 use crate::sudoku::*;
+use crate::undo::UndoTree;
 use crate::Dir::*;
 
-use std::time;
+use std::{collections::BTreeMap, time};
 
 /// the entire game logic state
 pub struct State {
     pub board: Board,
+    /// the unique solution of the puzzle.
+    /// `None` if the puzzle has more than one solution.
+    pub solution: Option<Board>,
     pub modifiable: [[bool; 9]; 9],
     pub markups: [[[bool; 9]; 9]; 9],
+    /// the color each cell was painted with in Visual mode.
+    /// `0` for unpainted cells, otherwise up to `PAINT_COLOR_COUNT`.
+    pub colors: [[u8; 9]; 9],
 
     pub preselection: u8,
     pub cur_row: usize,
@@ -16,24 +23,59 @@ pub struct State {
     pub mode: Mode,
     pub next_mode: Mode,
 
+    /// the corner of the rectangle selected in Visual mode opposite to the cursor
+    pub visual_anchor: (usize, usize),
+    /// the cells added to the selection with `stamp_selection()`
+    pub visual_selection: [[bool; 9]; 9],
+
     pub difficulty: Difficulty,
+    /// the seed the board was generated from.
+    /// `None` for puzzles that were not generated.
+    pub seed: Option<u64>,
+    /// whether the board was picked by the solving techniques it needs.
+    /// (see `State::new_game()`)
+    pub graded: bool,
     pub start_time: time::Instant,
-
-    pub undo_stack: Vec<DiffStep>,
-    pub redo_stack: Vec<DiffStep>,
+    pub hints_used: usize,
+
+    /// whether numbers breaking the sudoku rules are highlighted
+    pub highlight_conflicts: bool,
+
+    /// whether numbers that disagree with `solution` are highlighted
+    /// and counted as `mistakes` when placed.
+    pub check_mistakes: bool,
+    pub mistakes: usize,
+    /// the number of mistakes that end the game, if any.
+    /// mistakes are always checked if this is set.
+    pub max_mistakes: Option<usize>,
+
+    /// a message shown to the player below the board
+    pub message: String,
+    /// the command being typed after `:`, if any
+    pub command_line: Option<String>,
+
+    pub undo_tree: UndoTree,
+    /// changes in `undo_tree` named by the player, to jump back to.
+    pub checkpoints: BTreeMap<char, usize>,
+
+    /// the actions of the last change made to a cell, repeated with `.`
+    pub last_change: Vec<CellAction>,
+    last_change_cell: Option<(usize, usize)>,
 }
 
 impl State {
-    /// returns a new `State` with a randomly generated
-    /// sudoku `Board` of the provided `Difficulty`
-    pub fn init(difficulty: Difficulty) -> Self {
-        let board = generate_sudoku(difficulty);
+    /// returns a new `State` for playing the sudoku `board`
+    /// of the provided `Difficulty`, generated from `seed` if any.
+    /// the numbers already on the board become the unmodifiable givens.
+    pub fn init(board: Board, difficulty: Difficulty, seed: Option<u64>) -> Self {
         let modifiable = State::init_modifiables(board);
 
         Self {
             board,
+            solution: State::init_solution(board),
             modifiable,
             markups: [[[false; 9]; 9]; 9],
+            colors: [[0; 9]; 9],
 
             preselection: 1,
             cur_row: 4,
@@ -42,14 +84,66 @@ impl State {
             mode: Mode::default(),
             next_mode: Mode::default(),
 
+            visual_anchor: (4, 4),
+            visual_selection: [[false; 9]; 9],
+
             difficulty,
+            seed,
+            graded: false,
             start_time: time::Instant::now(),
+            hints_used: 0,
+
+            highlight_conflicts: true,
+
+            check_mistakes: false,
+            mistakes: 0,
+            max_mistakes: None,
+
+            message: String::new(),
+            command_line: None,
+
+            undo_tree: UndoTree::new(),
+            checkpoints: BTreeMap::new(),
 
-            undo_stack: Vec::with_capacity(160),
-            redo_stack: Vec::with_capacity(80),
+            last_change: Vec::new(),
+            last_change_cell: None,
         }
     }
 
+    /// returns a new `State` for playing a sudoku generated from `seed`.
+    /// the sudoku is picked by the solving techniques it needs if `graded` is set,
+    /// otherwise by its number of blanks. (see `generate_graded_sudoku()`)
+    pub fn new_game(difficulty: Difficulty, seed: u64, graded: bool) -> Self {
+        let board = match graded {
+            true => generate_graded_sudoku(seed, difficulty),
+            false => generate_sudoku(seed, difficulty),
+        };
+        let mut state = State::init(board, difficulty, Some(seed));
+        state.graded = graded;
+        state
+    }
+
+    /// returns the command line generating this sudoku again, if it was generated.
+    pub fn replay_command(&self) -> Option<String> {
+        let seed = self.seed?;
+        let difficulty = match self.difficulty {
+            Difficulty::Custom(x) => x.to_string(),
+            d => d.to_string().to_lowercase(),
+        };
+        let graded = match self.graded {
+            true => " -g",
+            false => "",
+        };
+        Some(format!("shdoku -d {difficulty}{graded} -s {seed}"))
+    }
+
+    /// returns a new `State` for playing a given puzzle,
+    /// with a custom difficulty of its number of blanks.
+    pub fn from_puzzle(board: Board) -> Self {
+        let blanks = board.iter().flatten().filter(|&&cell| cell == 0).count();
+        State::init(board, Difficulty::Custom(blanks), None)
+    }
+
     /// returns a boolean mask of the board, indicating which cells
     /// can be modified by the user and which are part of the puzzle constraints.
     /// makes only cells that are initialized with the value 0 modifiable
@@ -65,6 +159,39 @@ impl State {
         modifiable
     }
 
+    /// returns the solution of `puzzle` if it is unique.
+    pub fn init_solution(puzzle: Board) -> Option<Board> {
+        match count_solutions(&puzzle, 2) {
+            (SolutionCount::One, solutions) => solutions.first().copied(),
+            _ => None,
+        }
+    }
+
+    /// returns the puzzle being played, with only the givens on the board.
+    pub fn get_puzzle(&self) -> Board {
+        let mut puzzle = self.board;
+        for (puzzle_row, modifiable_row) in puzzle.iter_mut().zip(self.modifiable.iter()) {
+            for (cell, &modifiable) in puzzle_row.iter_mut().zip(modifiable_row.iter()) {
+                if modifiable {
+                    *cell = 0;
+                }
+            }
+        }
+        puzzle
+    }
+
+    /// returns the current game as text: the puzzle and the current board
+    /// in the 81-character line format, followed by grids of the board and the marks.
+    pub fn export(&self) -> String {
+        format!(
+            "puzzle: {}\nboard:  {}\n\n{}\n{}",
+            format_line(&self.get_puzzle()),
+            format_line(&self.board),
+            format_grid(&self.board),
+            format_marks(&self.board, &self.markups)
+        )
+    }
+
     pub fn current_cell_is_modifiable(&self) -> bool {
         self.modifiable[self.cur_row][self.cur_col]
     }
@@ -100,44 +227,94 @@ impl State {
     pub fn toggle_current_cell(&mut self) {
         if self.current_cell_is_modifiable() {
             if *self.current_cell() == self.preselection {
-                self.delete_current_cell();
+                self.act(CellAction::Delete);
             } else {
-                let old_num = *self.current_cell();
-                *self.current_cell() = self.preselection;
-
-                let affected =
-                    self.delete_colliding_marks(self.preselection, self.cur_row, self.cur_col);
-
-                self.push_to_undos_invalidating_redos(DiffStep::Edit(
-                    old_num,
-                    (self.cur_row, self.cur_col),
-                    affected,
-                    self.preselection,
-                ))
+                self.act(CellAction::Place(self.preselection));
             }
         }
     }
 
-    pub fn delete_current_cell(&mut self) {
-        if self.current_cell_is_modifiable() {
-            let cur = self.current_cell();
-            let old = *cur;
-            *cur = 0;
+    /// applies `action` to the current cell and records it for `repeat_last_change()`.
+    /// actions on the same cell in a row are recorded as one change.
+    fn act(&mut self, action: CellAction) {
+        if !self.apply_cell_action(action) {
+            return;
+        }
+        let cell = (self.cur_row, self.cur_col);
+        if self.last_change_cell != Some(cell) {
+            self.last_change.clear();
+            self.last_change_cell = Some(cell);
+        }
+        self.last_change.push(action);
+    }
+
+    /// applies `action` to the current cell if possible.
+    /// returns whether it was applied.
+    fn apply_cell_action(&mut self, action: CellAction) -> bool {
+        let (row, col) = (self.cur_row, self.cur_col);
+        match action {
+            CellAction::Place(num) if self.modifiable[row][col] && self.board[row][col] != num => {
+                self.place_num(row, col, num)
+            }
+            CellAction::Delete if self.modifiable[row][col] => {
+                let old = self.board[row][col];
+                self.board[row][col] = 0;
+                self.push_undo_step(DiffStep::Edit(old, (row, col), vec![], 0));
+            }
+            CellAction::Mark(num, mark) if self.board[row][col] == 0 => {
+                self.set_mark(row, col, num, mark)
+            }
+            _ => return false,
+        }
+        true
+    }
+
+    /// applies the last change made to a cell to the current cell,
+    /// as a single undo step. (like vim's `.`)
+    pub fn repeat_last_change(&mut self) {
+        if self.last_change.is_empty() {
+            self.message = String::from("No change to repeat");
+            return;
+        }
+
+        let first_change = self.undo_tree.next_node();
+        for action in self.last_change.clone() {
+            self.apply_cell_action(action);
+        }
+        self.undo_tree.group_since(first_change);
 
-            self.push_to_undos_invalidating_redos(DiffStep::Edit(
-                old,
-                (self.cur_row, self.cur_col),
-                vec![],
-                0,
-            ));
+        self.last_change_cell = None;
+    }
+
+    /// places `num` in the cell at `row`, `col` and deletes the marks it invalidates.
+    /// counts a mistake if mistakes are checked and `num` disagrees with the solution.
+    fn place_num(&mut self, row: usize, col: usize, num: u8) {
+        let old_num = self.board[row][col];
+        self.board[row][col] = num;
+
+        if self.is_mistake(row, col) {
+            self.mistakes += 1;
+            self.message = match self.max_mistakes {
+                Some(max) => format!("Mistake! ({}/{max})", self.mistakes),
+                None => String::from("Mistake!"),
+            };
         }
+
+        let affected = self.delete_colliding_marks(num, row, col);
+
+        self.push_undo_step(DiffStep::Edit(old_num, (row, col), affected, num))
+    }
+
+    pub fn delete_current_cell(&mut self) {
+        self.act(CellAction::Delete);
     }
 
     /// deletes all marks of `num` in it's row, column and block.
     /// used to automatically remove marks when placing a number that
     /// invalidates those marks.
     ///
-    /// returns a vector of the affected marks board positions
+    /// returns a vector of the affected marks board positions,
+    /// each position only once.
     pub fn delete_colliding_marks(
         &mut self,
         num: u8,
@@ -147,25 +324,40 @@ impl State {
         let mut deleted = Vec::new();
 
         for i in 0..9 {
-            if self.markups[i][col][num as usize - 1] {
-                deleted.push((i, col));
-            }
-            if self.markups[row][i][num as usize - 1] {
-                deleted.push((row, i));
-            }
-            // TODO: avoid doubly including marks that are both in the same block AND row/col
-            if self.markups[row / 3 * 3 + i / 3][col / 3 * 3 + i % 3][num as usize - 1] {
-                deleted.push((row / 3 * 3 + i / 3, col / 3 * 3 + i % 3));
+            for (r, c) in [
+                (i, col),
+                (row, i),
+                (row / 3 * 3 + i / 3, col / 3 * 3 + i % 3),
+            ] {
+                if self.markups[r][c][num as usize - 1] {
+                    self.markups[r][c][num as usize - 1] = false;
+                    deleted.push((r, c));
+                }
             }
-
-            self.markups[i][col][num as usize - 1] = false;
-            self.markups[row][i][num as usize - 1] = false;
-            self.markups[row / 3 * 3 + i / 3][col / 3 * 3 + i % 3][num as usize - 1] = false;
         }
 
         deleted
     }
 
+    /// toggles the preselected number, or mark in Markup mode, in `count` cells
+    /// starting at the cursor and going right, as a single undo step.
+    /// the cursor stays on the last of them, like with vim's `3r`.
+    ///
+    /// NOTE: stops at the end of the row.
+    pub fn toggle_cells(&mut self, count: usize) {
+        let first_change = self.undo_tree.next_node();
+        for i in 0..count.min(9 - self.cur_col) {
+            if i > 0 {
+                self.move_cursor(Right);
+            }
+            match self.mode {
+                Mode::Markup => self.toggle_current_mark(),
+                _ => self.toggle_current_cell(),
+            }
+        }
+        self.undo_tree.group_since(first_change);
+    }
+
     pub fn toggle_current_mark(&mut self) {
         if self.markups[self.cur_row][self.cur_col][self.preselection as usize - 1] {
             self.delete_current_mark();
@@ -175,33 +367,150 @@ impl State {
     }
 
     pub fn delete_current_mark(&mut self) {
-        if *self.current_cell() != 0 {
+        self.act(CellAction::Mark(self.preselection, false));
+    }
+
+    pub fn set_current_mark(&mut self) {
+        self.act(CellAction::Mark(self.preselection, true));
+    }
+
+    /// sets or unsets the mark of `num` in the cell at `row`, `col`.
+    fn set_mark(&mut self, row: usize, col: usize, num: u8, mark: bool) {
+        let marked = self.markups[row][col][num as usize - 1];
+        self.markups[row][col][num as usize - 1] = mark;
+
+        self.push_undo_step(DiffStep::Mark(num, (row, col), marked));
+    }
+
+    /// marks all numbers that are not yet placed in the row, column or block
+    /// of each empty cell, and unmarks all others, as a single undo step.
+    pub fn fill_all_candidates(&mut self) {
+        self.fill_candidates(all_cells());
+    }
+
+    /// like `fill_all_candidates()`, but only for the cells
+    /// in the row, column and block of the cursor.
+    pub fn fill_current_candidates(&mut self) {
+        let (row, col) = (self.cur_row, self.cur_col);
+        let cells = all_cells()
+            .filter(|&(r, c)| r == row || c == col || (r / 3, c / 3) == (row / 3, col / 3));
+        self.fill_candidates(cells);
+    }
+
+    fn fill_candidates(&mut self, cells: impl Iterator<Item = (usize, usize)>) {
+        let candidates = logic::candidates(&self.board);
+        let mut toggled = Vec::new();
+
+        for (r, c) in cells.filter(|&(r, c)| self.board[r][c] == 0) {
+            for num in 1..=9 {
+                let candidate = candidates[r][c] & (1 << num) != 0;
+                if self.markups[r][c][num as usize - 1] != candidate {
+                    self.markups[r][c][num as usize - 1] = candidate;
+                    toggled.push((num, (r, c)));
+                }
+            }
+        }
+
+        if toggled.is_empty() {
+            self.message = String::from("All candidates are already marked");
             return;
         }
+        self.message = format!("Filled in candidates, {} marks changed", toggled.len());
+        self.push_undo_step(DiffStep::Marks(toggled));
+    }
 
-        let marked = self.markups[self.cur_row][self.cur_col][self.preselection as usize - 1];
-        self.markups[self.cur_row][self.cur_col][self.preselection as usize - 1] = false;
+    /// asks the logical solver for the next deduction and applies it,
+    /// leaving a description of it in `message`.
+    ///
+    /// the player's marks narrow down the candidates of a cell,
+    /// unless they rule out the number of the solution.
+    /// cells without any marks, or with marks ruling out the solution,
+    /// are treated as having all numbers as candidates that are not yet
+    /// placed in their row, column or block.
+    /// eliminations in such cells replace their marks with the remaining candidates.
+    ///
+    /// NOTE: a wrong number would lead to wrong deductions, so instead of
+    ///       a deduction the first wrong number is pointed out, which also
+    ///       counts as a hint. without a unique solution, only numbers
+    ///       that conflict with each other are found.
+    pub fn hint(&mut self) {
+        if let Some((r, c)) = self.first_wrong_cell() {
+            self.hints_used += 1;
+            self.message = format!("r{}c{} is wrong, fix it first", r + 1, c + 1);
+            return;
+        }
 
-        self.push_to_undos_invalidating_redos(DiffStep::Mark(
-            self.preselection,
-            (self.cur_row, self.cur_col),
-            marked,
-        ));
-    }
+        let mut candidates = logic::candidates(&self.board);
+        for (r, row) in candidates.iter_mut().enumerate() {
+            for (c, cell) in row.iter_mut().enumerate() {
+                let marks = self.marks_mask(r, c);
+                if marks != 0 && !self.marks_rule_out_solution(r, c) {
+                    *cell &= marks;
+                }
+            }
+        }
 
-    pub fn set_current_mark(&mut self) {
-        if *self.current_cell() != 0 {
+        let Some(step) = logic::next_step(&candidates) else {
+            self.message = String::from("No hint available.");
             return;
+        };
+
+        let first_change = self.undo_tree.next_node();
+
+        for &(r, c, x) in &step.placements {
+            self.place_num(r, c, x);
+        }
+
+        for &(r, c, x) in &step.eliminations {
+            if self.marks_mask(r, c) == 0 || self.marks_rule_out_solution(r, c) {
+                for num in 1..=9 {
+                    let candidate = candidates[r][c] & (1 << num) != 0;
+                    if self.markups[r][c][num as usize - 1] != candidate {
+                        self.set_mark(r, c, num, candidate);
+                    }
+                }
+            }
+            if self.markups[r][c][x as usize - 1] {
+                self.set_mark(r, c, x, false);
+            }
         }
 
-        let marked = self.markups[self.cur_row][self.cur_col][self.preselection as usize - 1];
-        self.markups[self.cur_row][self.cur_col][self.preselection as usize - 1] = true;
+        self.undo_tree.group_since(first_change);
 
-        self.push_to_undos_invalidating_redos(DiffStep::Mark(
-            self.preselection,
-            (self.cur_row, self.cur_col),
-            marked,
-        ));
+        self.hints_used += 1;
+        self.message = step.to_string();
+    }
+
+    /// returns the first number that disagrees with the solution,
+    /// or that conflicts with another one if there is no unique solution.
+    fn first_wrong_cell(&self) -> Option<(usize, usize)> {
+        match self.solution {
+            Some(solution) => all_cells()
+                .find(|&(r, c)| self.board[r][c] != 0 && self.board[r][c] != solution[r][c]),
+            None => find_conflicts(&self.board)
+                .into_iter()
+                .flat_map(|conflict| conflict.cells)
+                .filter(|&(r, c)| self.modifiable[r][c])
+                .min(),
+        }
+    }
+
+    /// returns whether the cell at `row`, `col` has marks,
+    /// but none for the number of the solution.
+    fn marks_rule_out_solution(&self, row: usize, col: usize) -> bool {
+        let marks = self.marks_mask(row, col);
+        marks != 0
+            && self
+                .solution
+                .is_some_and(|solution| marks & (1 << solution[row][col]) == 0)
+    }
+
+    /// returns the marks of the cell at `row`, `col` as a candidate bitmask.
+    /// (see `logic::Candidates`)
+    fn marks_mask(&self, row: usize, col: usize) -> u16 {
+        (1..=9)
+            .filter(|&x| self.markups[row][col][x - 1])
+            .fold(0, |mask, x| mask | (1 << x))
     }
 
     pub fn move_cursor_to(&mut self, row: usize, col: usize) {
@@ -215,7 +524,7 @@ impl State {
 
     pub fn enter_mode(&mut self, mode: Mode) {
         match mode {
-            Mode::Go => {
+            Mode::Go | Mode::Visual => {
                 self.enter_mode_once(mode);
                 return;
             }
@@ -229,19 +538,193 @@ impl State {
     /// then returning to the previous mode
     pub fn enter_mode_once(&mut self, mode: Mode) {
         if self.mode != mode {
+            if mode == Mode::Visual {
+                self.visual_anchor = (self.cur_row, self.cur_col);
+                self.visual_selection = [[false; 9]; 9];
+            }
             self.next_mode = self.mode;
             self.mode = mode;
         }
     }
 
+    /// returns a mask of the cells selected in Visual mode:
+    /// the rectangle between `visual_anchor` and the cursor,
+    /// and the cells added with `stamp_selection()`.
+    /// all cells are `false` outside of Visual mode.
+    pub fn get_selection(&self) -> [[bool; 9]; 9] {
+        let mut selection = self.visual_selection;
+        if self.mode == Mode::Visual {
+            let (anchor_row, anchor_col) = self.visual_anchor;
+            let rows = anchor_row.min(self.cur_row)..=anchor_row.max(self.cur_row);
+            for row in &mut selection[rows] {
+                let cols = anchor_col.min(self.cur_col)..=anchor_col.max(self.cur_col);
+                row[cols].iter_mut().for_each(|cell| *cell = true);
+            }
+        } else {
+            selection = [[false; 9]; 9];
+        }
+        selection
+    }
+
+    /// returns the positions of the cells selected in Visual mode.
+    fn selected_cells(&self) -> Vec<(usize, usize)> {
+        let selection = self.get_selection();
+        all_cells().filter(|&(r, c)| selection[r][c]).collect()
+    }
+
+    /// adds the selected rectangle to the selection and starts
+    /// a new rectangle at the cursor, to select any set of cells.
+    pub fn stamp_selection(&mut self) {
+        self.visual_selection = self.get_selection();
+        self.visual_anchor = (self.cur_row, self.cur_col);
+    }
+
+    /// marks the preselected number in all empty selected cells
+    /// as a single undo step, then leaves Visual mode.
+    pub fn mark_selection(&mut self) {
+        let first_change = self.undo_tree.next_node();
+        let num = self.preselection;
+        for (r, c) in self.selected_cells() {
+            if self.board[r][c] == 0 && !self.markups[r][c][num as usize - 1] {
+                self.set_mark(r, c, num, true);
+            }
+        }
+        self.undo_tree.group_since(first_change);
+        self.enter_next_mode();
+    }
+
+    /// deletes all marks in the selected cells as a single undo step,
+    /// then leaves Visual mode.
+    pub fn clear_selection_marks(&mut self) {
+        let first_change = self.undo_tree.next_node();
+        for (r, c) in self.selected_cells() {
+            for num in 1..=9 {
+                if self.markups[r][c][num as usize - 1] {
+                    self.set_mark(r, c, num, false);
+                }
+            }
+        }
+        self.undo_tree.group_since(first_change);
+        self.enter_next_mode();
+    }
+
+    /// paints the selected cells with the color following the one
+    /// of the first selected cell, going back to unpainted after the last one.
+    /// painting is a single undo step and leaves Visual mode.
+    pub fn paint_selection(&mut self) {
+        let cells = self.selected_cells();
+        let Some(&(row, col)) = cells.first() else {
+            return;
+        };
+        let color = (self.colors[row][col] + 1) % (PAINT_COLOR_COUNT + 1);
+
+        let first_change = self.undo_tree.next_node();
+        for (r, c) in cells {
+            if self.colors[r][c] != color {
+                let old_color = self.colors[r][c];
+                self.colors[r][c] = color;
+                self.push_undo_step(DiffStep::Paint(old_color, (r, c)));
+            }
+        }
+        self.undo_tree.group_since(first_change);
+        self.enter_next_mode();
+    }
+
     pub fn enter_next_mode(&mut self) {
         self.mode = self.next_mode;
     }
 
-    fn get_elapsed_time(&self) -> time::Duration {
+    pub fn get_elapsed_time(&self) -> time::Duration {
         time::Instant::now() - self.start_time
     }
 
+    /// moves `start_time` back so that `elapsed` time has passed since it.
+    /// used to continue the timer of a saved game.
+    ///
+    /// NOTE: fails if `elapsed` reaches back further than the clock can.
+    pub fn set_elapsed_time(&mut self, elapsed: time::Duration) -> Result<(), String> {
+        self.start_time = time::Instant::now()
+            .checked_sub(elapsed)
+            .ok_or(format!("Can't continue a timer of {}s.", elapsed.as_secs()))?;
+        Ok(())
+    }
+
+    /// returns a mask of the cells whose number occurs more than once
+    /// in their row, column or block.
+    /// all cells are `false` if `highlight_conflicts` is turned off.
+    pub fn get_conflict_mask(&self) -> [[bool; 9]; 9] {
+        let mut mask = [[false; 9]; 9];
+        if self.highlight_conflicts {
+            for conflict in find_conflicts(&self.board) {
+                for (r, c) in conflict.cells {
+                    mask[r][c] = true;
+                }
+            }
+        }
+        mask
+    }
+
+    pub fn toggle_conflict_highlighting(&mut self) {
+        self.highlight_conflicts = !self.highlight_conflicts;
+        self.message = match self.highlight_conflicts {
+            true => String::from("Conflict highlighting on"),
+            false => String::from("Conflict highlighting off"),
+        };
+    }
+
+    /// returns whether mistakes are checked and the number in the cell
+    /// at `row`, `col` disagrees with the solution.
+    pub fn is_mistake(&self, row: usize, col: usize) -> bool {
+        let num = self.board[row][col];
+        match self.solution {
+            Some(solution) if self.check_mistakes => num != 0 && num != solution[row][col],
+            _ => false,
+        }
+    }
+
+    /// returns a mask of the cells for which `is_mistake()` holds.
+    pub fn get_mistake_mask(&self) -> [[bool; 9]; 9] {
+        let mut mask = [[false; 9]; 9];
+        for (r, row) in mask.iter_mut().enumerate() {
+            for (c, cell) in row.iter_mut().enumerate() {
+                *cell = self.is_mistake(r, c);
+            }
+        }
+        mask
+    }
+
+    pub fn toggle_mistake_checking(&mut self) {
+        if self.max_mistakes.is_some() {
+            self.message = String::from("Mistakes are always checked in this game");
+            return;
+        }
+        if self.solution.is_none() {
+            self.message = String::from("Can't check mistakes, the puzzle has no unique solution");
+            return;
+        }
+        self.check_mistakes = !self.check_mistakes;
+        self.message = match self.check_mistakes {
+            true => String::from("Mistake checking on"),
+            false => String::from("Mistake checking off"),
+        };
+    }
+
+    /// returns whether the player made as many mistakes as allowed.
+    pub fn is_game_over(&self) -> bool {
+        self.max_mistakes.is_some_and(|max| self.mistakes >= max)
+    }
+
+    /// returns the mistake counter used on the ingame scoreboard,
+    /// padded to a width of 5 characters.
+    /// returns dashes if mistakes are not checked.
+    pub fn get_mistakes_string(&self) -> String {
+        match (self.check_mistakes, self.max_mistakes) {
+            (false, _) => String::from(" --  "),
+            (true, Some(max)) => format!("{:>2}/{:<2}", self.mistakes.min(99), max.min(99)),
+            (true, None) => format!("{:>2}   ", self.mistakes.min(99)),
+        }
+    }
+
     /// returns number of filled cells
     pub fn get_completion_string(&self) -> String {
         let mut count = 0;
@@ -318,56 +801,151 @@ impl State {
 
     /// undo an action that has been taken
     pub fn undo(&mut self) {
-        self.apply_diff(DiffType::Undo);
+        if let Some(node) = self.undo_tree.undo_target() {
+            self.move_in_undo_tree(node);
+        }
     }
 
     /// redo an action if one was taken and undone.
+    /// redoes the most recently visited branch if there are several.
     pub fn redo(&mut self) {
-        self.apply_diff(DiffType::Redo);
+        if let Some(node) = self.undo_tree.redo_target() {
+            self.move_in_undo_tree(node);
+        }
     }
 
-    /// apply a undo/redo step diff and get back the inverse step
-    pub fn apply_diff(&mut self, diff_type: DiffType) {
-        use DiffStep::*;
-        use DiffType::*;
+    /// moves back to the board as it was before the previous change
+    /// in the order the changes were made, across branches. (like vim's `g-`)
+    pub fn undo_chronologically(&mut self) {
+        match self.undo_tree.current() {
+            0 => self.message = String::from("Already at the oldest change"),
+            node => self.move_in_undo_tree(node - 1),
+        }
+    }
 
-        let (used_stack, other_stack) = match diff_type {
-            Redo => (&mut self.redo_stack, &mut self.undo_stack),
-            Undo => (&mut self.undo_stack, &mut self.redo_stack),
-        };
+    /// moves forward to the board as it was after the next change
+    /// in the order the changes were made, across branches. (like vim's `g+`)
+    pub fn redo_chronologically(&mut self) {
+        match self.undo_tree.current() + 1 {
+            node if node < self.undo_tree.next_node() => self.move_in_undo_tree(node),
+            _ => self.message = String::from("Already at the newest change"),
+        }
+    }
 
-        if let Some(diff) = used_stack.pop() {
-            match diff {
-                Edit(original, (r, c), marks, replacement) => {
-                    self.board[r][c] = original;
-                    let affected_mark_num = match diff_type {
-                        Redo => original,
-                        Undo => replacement,
-                    };
-
-                    if affected_mark_num != 0 {
-                        marks.iter().for_each(|&(r, c)| {
-                            self.markups[r][c][affected_mark_num as usize - 1] =
-                                !self.markups[r][c][affected_mark_num as usize - 1]
-                        });
-                    }
+    /// lists the tips of the branches of the undo tree in `message`
+    /// with their number of changes.
+    pub fn list_branches(&mut self) {
+        let branches = self
+            .undo_tree
+            .leaves()
+            .into_iter()
+            .map(|node| match self.undo_tree.depth(node) {
+                1 => format!("{node} (1 change)"),
+                depth => format!("{node} ({depth} changes)"),
+            })
+            .collect::<Vec<_>>();
+        self.message = format!(
+            "Branches: {}; at change {}",
+            branches.join(", "),
+            self.undo_tree.current()
+        );
+    }
 
-                    other_stack.push(DiffStep::Edit(replacement, (r, c), marks, original));
+    /// names the current change in the undo tree `name`, like vim's `m`.
+    /// only letters can be used as names.
+    pub fn set_checkpoint(&mut self, name: char) {
+        if !name.is_ascii_alphabetic() {
+            self.message = format!("Invalid checkpoint name '{name}', use a letter");
+            return;
+        }
+        self.checkpoints.insert(name, self.undo_tree.current());
+        self.message = format!("Checkpoint {name} set");
+    }
+
+    /// moves to the change named `name` with `set_checkpoint()`,
+    /// restoring the board and marks as they were.
+    ///
+    /// NOTE: the jump is not a change itself and can't be undone.
+    ///       undoing and `g-`/`g+` go on from the checkpoint's change,
+    ///       and a new change starts a branch there, keeping the changes
+    ///       made after it in the undo tree.
+    pub fn jump_to_checkpoint(&mut self, name: char) {
+        match self.checkpoints.get(&name) {
+            Some(&node) => {
+                self.move_in_undo_tree(node);
+                self.message = format!("Jumped to checkpoint {name}");
+            }
+            None => self.message = format!("No checkpoint {name}"),
+        }
+    }
+
+    /// applies the steps needed to get from the current node
+    /// of the undo tree to `node`.
+    fn move_in_undo_tree(&mut self, node: usize) {
+        for (step_node, diff_type) in self.undo_tree.path(node) {
+            let step = self
+                .undo_tree
+                .replace_step(step_node, DiffStep::Group(Vec::new()));
+            let inverse = self.apply_step(step, diff_type);
+            self.undo_tree.replace_step(step_node, inverse);
+        }
+        self.undo_tree.set_current(node);
+    }
+
+    /// applies a single undo/redo step and returns its inverse.
+    /// the steps of a `Group` are applied from last to first,
+    /// so its inverse lists the inverted steps in the order they were applied.
+    fn apply_step(&mut self, step: DiffStep, diff_type: DiffType) -> DiffStep {
+        use DiffStep::*;
+        use DiffType::*;
+
+        match step {
+            Edit(original, (r, c), marks, replacement) => {
+                self.board[r][c] = original;
+                let affected_mark_num = match diff_type {
+                    Redo => original,
+                    Undo => replacement,
+                };
+
+                if affected_mark_num != 0 {
+                    marks.iter().for_each(|&(r, c)| {
+                        self.markups[r][c][affected_mark_num as usize - 1] =
+                            !self.markups[r][c][affected_mark_num as usize - 1]
+                    });
                 }
-                Mark(num, (r, c), mark) => {
-                    let old_mark = self.markups[r][c][num as usize - 1];
-                    self.markups[r][c][num as usize - 1] = mark;
-                    other_stack.push(DiffStep::Mark(self.preselection, (r, c), old_mark));
+
+                Edit(replacement, (r, c), marks, original)
+            }
+            Mark(num, (r, c), mark) => {
+                let old_mark = self.markups[r][c][num as usize - 1];
+                self.markups[r][c][num as usize - 1] = mark;
+                Mark(num, (r, c), old_mark)
+            }
+            Paint(color, (r, c)) => {
+                let old_color = self.colors[r][c];
+                self.colors[r][c] = color;
+                Paint(old_color, (r, c))
+            }
+            Marks(toggled) => {
+                for &(num, (r, c)) in &toggled {
+                    self.markups[r][c][num as usize - 1] = !self.markups[r][c][num as usize - 1];
                 }
+                Marks(toggled)
             }
+            Group(steps) => Group(
+                steps
+                    .into_iter()
+                    .rev()
+                    .map(|step| self.apply_step(step, diff_type))
+                    .collect(),
+            ),
         }
     }
 
-    /// pushes a move done by the player to the undo stack
-    /// additionally invalidating the redo stack
-    pub fn push_to_undos_invalidating_redos(&mut self, undo_step: DiffStep) {
-        self.undo_stack.push(undo_step);
-        self.redo_stack.clear();
+    /// pushes a move done by the player to the undo tree,
+    /// starting a new branch if other moves had been undone.
+    pub fn push_undo_step(&mut self, undo_step: DiffStep) {
+        self.undo_tree.push(undo_step);
     }
 }
 
@@ -388,6 +966,20 @@ pub enum Mode {
     Edit,
     Markup,
     Go,
+    Visual,
+}
+
+/// the number of colors cells can be painted with in Visual mode.
+pub const PAINT_COLOR_COUNT: u8 = 3;
+
+/// an action of the player on the current cell.
+/// (see `State::repeat_last_change()`)
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum CellAction {
+    Place(u8),
+    Delete,
+    /// `Mark(num, mark)`
+    Mark(u8, bool),
 }
 
 #[derive(Debug, PartialEq, Clone)]
@@ -397,6 +989,15 @@ pub enum DiffStep {
 
     /// `Mark(num, position, mark)`
     Mark(u8, (usize, usize), bool),
+
+    /// `Paint(color, position)`
+    Paint(u8, (usize, usize)),
+
+    /// `Marks(toggled_marks)`, with each toggled mark as `(num, position)`
+    Marks(Vec<(u8, (usize, usize))>),
+
+    /// `Group(steps)`, several steps that are undone and redone at once
+    Group(Vec<DiffStep>),
 }
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
@@ -0,0 +1,113 @@
+use crate::sudoku::{count_solutions, find_conflicts, Board, New, SolutionCount};
+
+/// parses a sudoku from the common 81-character line format,
+/// listing the cells row by row with `0` or `.` for blank cells.
+/// surrounding whitespace is ignored.
+///
+/// returns an error message if the line has the wrong length,
+/// contains other characters, or the puzzle breaks the sudoku rules.
+pub fn parse_board(line: &str) -> Result<Board, String> {
+    let board = parse_line(line)?;
+
+    if let Some(conflict) = find_conflicts(&board).first() {
+        return Err(format!("The puzzle breaks the rules: {conflict}."));
+    }
+
+    if count_solutions(&board, 1).0 == SolutionCount::Zero {
+        return Err(String::from("The puzzle has no solution."));
+    }
+
+    Ok(board)
+}
+
+/// parses a sudoku from the 81-character line format like `parse_board()`,
+/// without checking whether the numbers follow the sudoku rules.
+pub fn parse_line(line: &str) -> Result<Board, String> {
+    let line = line.trim();
+
+    let len = line.chars().count();
+    if len != 81 {
+        return Err(format!("Expected 81 cells, but got {len}."));
+    }
+
+    let mut board = Board::new();
+    for (i, chr) in line.chars().enumerate() {
+        board[i / 9][i % 9] = match chr {
+            '.' | '0' => 0,
+            '1'..='9' => chr as u8 - b'0',
+            _ => {
+                return Err(format!(
+                    "Invalid character '{chr}' at position {}. Use 1-9 for numbers and 0 or . for blank cells.",
+                    i + 1
+                ))
+            }
+        };
+    }
+
+    Ok(board)
+}
+
+/// formats a sudoku in the 81-character line format,
+/// using `.` for blank cells. (see `parse_board()`)
+pub fn format_line(board: &Board) -> String {
+    board
+        .iter()
+        .flatten()
+        .map(|&cell| match cell {
+            0 => '.',
+            x => (x + b'0') as char,
+        })
+        .collect()
+}
+
+/// formats a sudoku as a grid like the one drawn in the game.
+pub fn format_grid(board: &Board) -> String {
+    format_boxes(8, |r, block| {
+        let mut line = String::new();
+        for &cell in &board[r][block * 3..block * 3 + 3] {
+            line.push(' ');
+            line.push(match cell {
+                0 => ' ',
+                x => (x + b'0') as char,
+            });
+        }
+        line + "  "
+    })
+}
+
+/// formats the marks of a sudoku as a grid, listing the marked numbers of each empty cell.
+pub fn format_marks(board: &Board, markups: &[[[bool; 9]; 9]; 9]) -> String {
+    format_boxes(31, |r, block| {
+        let mut line = String::new();
+        for c in block * 3..block * 3 + 3 {
+            let marks = (1..=9u8)
+                .filter(|&x| board[r][c] == 0 && markups[r][c][x as usize - 1])
+                .map(|x| (x + b'0') as char)
+                .collect::<String>();
+            line.push_str(&format!(" {:<9}", marks));
+        }
+        line + " "
+    })
+}
+
+/// draws box outlines around 9 rows of 3 blocks each, with `block_width`
+/// columns per block. `format_block(row, block)` returns the inside of a block.
+fn format_boxes(block_width: usize, format_block: impl Fn(usize, usize) -> String) -> String {
+    let border = |left, middle, right| {
+        let edge = "─".repeat(block_width);
+        format!("{left}{edge}{middle}{edge}{middle}{edge}{right}\n")
+    };
+
+    let mut grid = border('┌', '┬', '┐');
+    for r in 0..9 {
+        if r == 3 || r == 6 {
+            grid.push_str(&border('├', '┼', '┤'));
+        }
+        for block in 0..3 {
+            grid.push('│');
+            grid.push_str(&format_block(r, block));
+        }
+        grid.push_str("│\n");
+    }
+    grid + &border('└', '┴', '┘')
+}
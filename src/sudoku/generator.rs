@@ -1,14 +1,18 @@
 use crate::generator::Difficulty::*;
-use crate::rand::{seq::SliceRandom, thread_rng};
-use crate::sudoku::{Board, New};
+use crate::rand::{seq::SliceRandom, thread_rng, Rng, SeedableRng};
+use crate::rand_chacha::ChaCha8Rng;
+use crate::sudoku::{all_cells, grade, Board, New};
 
 use std::fmt;
 
 /// categories of difficulty, indicating how many
-/// empty spaces will be on a sudoku board.
+/// empty spaces will be on a sudoku board at most.
 /// (see `Difficulty::removal_count()` for values)
+///
+/// the named difficulties are also used to grade sudokus
+/// by the solving techniques they require (see `grade()`).
 #[allow(unused)]
-#[derive(Default, Copy, Clone, PartialEq)]
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub enum Difficulty {
     #[default]
     Easy,
@@ -21,6 +25,10 @@ pub enum Difficulty {
 impl Difficulty {
     /// the number of cells to be deleted from a filled
     /// sudoku board of the given `Difficulty`
+    ///
+    /// NOTE: this is only reached if every removal keeps the solution
+    ///       unique. `generate_sudoku()` gives up after a few attempts,
+    ///       so `Expert` boards usually end up with fewer (~55-58) holes.
     pub fn removal_count(&self) -> usize {
         match self {
             Easy => 31,
@@ -61,43 +69,277 @@ impl fmt::Display for Difficulty {
     }
 }
 
+/// returns a random seed for the sudoku generators
+pub fn random_seed() -> u64 {
+    thread_rng().gen()
+}
+
 /// generate a random, unsolved sudoku board with a given `Difficulty`.
-/// solves a empty sudoku with random cell order and then removes
-/// some number of cells depending on the difficulty.
-pub fn generate_sudoku(difficulty: Difficulty) -> Board {
-    let mut board = Board::new();
+/// the same `seed` always generates the same board.
+/// (see `generate_sudoku_with_rng()`)
+///
+/// NOTE: `ChaCha8Rng` is used since its output for a seed is fixed across
+///       rand versions and platforms, unlike that of `StdRng`.
+pub fn generate_sudoku(seed: u64, difficulty: Difficulty) -> Board {
+    generate_sudoku_with_rng(&mut ChaCha8Rng::seed_from_u64(seed), difficulty)
+}
 
-    while solve_random(&mut board).is_err() {}
+/// generate a random, unsolved sudoku board with a given `Difficulty`
+/// using `rng` as the source of randomness.
+/// solves a empty sudoku with random cell order and then digs holes
+/// in random order, keeping only the removals after which the board
+/// still has exactly one solution.
+///
+/// NOTE: `Difficulty::removal_count()` is an upper bound here.
+///       random digging rarely reaches more than ~58 holes, so the board
+///       with the most holes out of a few attempts is returned
+///       if the target can not be reached.
+pub fn generate_sudoku_with_rng<R: Rng>(rng: &mut R, difficulty: Difficulty) -> Board {
+    const ATTEMPTS: usize = 8;
 
     let removal_count = difficulty.removal_count();
+    let mut best = dig_holes(rng, removal_count);
+
+    for _ in 1..ATTEMPTS {
+        if best.1 == removal_count {
+            break;
+        }
+        let attempt = dig_holes(rng, removal_count);
+        if attempt.1 > best.1 {
+            best = attempt;
+        }
+    }
+
+    best.0
+}
+
+/// generate a random, unsolved sudoku board that is graded as the given
+/// `Difficulty` by `grade()`, instead of removing a fixed number of cells.
+/// the same `seed` always generates the same board.
+/// (see `generate_graded_sudoku_with_rng()`)
+pub fn generate_graded_sudoku(seed: u64, difficulty: Difficulty) -> Board {
+    generate_graded_sudoku_with_rng(&mut ChaCha8Rng::seed_from_u64(seed), difficulty)
+}
+
+/// generate a random, unsolved sudoku board that is graded as the given
+/// `Difficulty` by `grade()`, using `rng` as the source of randomness.
+/// holes are dug as long as the sudoku does not get harder than requested,
+/// retrying with a new board until the requested grade is reached.
+///
+/// NOTE: `Difficulty::Custom` can not be graded and falls
+///       back to `generate_sudoku_with_rng()`.
+///
+/// NOTE: only about one in four boards reaches `Expert`, so to never hang
+///       this gives up after `ATTEMPTS` boards and returns the hardest one
+///       found, which is then graded easier than requested.
+pub fn generate_graded_sudoku_with_rng<R: Rng>(rng: &mut R, difficulty: Difficulty) -> Board {
+    const ATTEMPTS: usize = 100;
+
+    if let Custom(_) = difficulty {
+        return generate_sudoku_with_rng(rng, difficulty);
+    }
+
+    let mut best = (Board::new(), None);
+
+    for _ in 0..ATTEMPTS {
+        let mut board = Board::new();
+
+        while solve_random(&mut board, rng).is_err() {}
+
+        let mut remove_positions = all_cells().collect::<Vec<_>>();
+        remove_positions.shuffle(rng);
+
+        for (row, col) in remove_positions {
+            let num = board[row][col];
+            board[row][col] = 0;
+            if grade(&board).is_none_or(|g| g > difficulty) {
+                board[row][col] = num;
+            }
+        }
+
+        let board_grade = grade(&board);
+        if board_grade == Some(difficulty) {
+            return board;
+        }
+        if best.1.is_none() || board_grade > best.1 {
+            best = (board, board_grade);
+        }
+    }
+
+    best.0
+}
+
+/// generates a fully solved board and removes up to `removal_count`
+/// cells from it, skipping any removal that would allow a second solution.
+///
+/// returns the resulting board and the number of removed cells
+fn dig_holes<R: Rng>(rng: &mut R, removal_count: usize) -> (Board, usize) {
+    let mut board = Board::new();
+
+    while solve_random(&mut board, rng).is_err() {}
 
-    let mut remove_positions = (0..81).map(|i| (i / 9, i % 9)).collect::<Vec<_>>();
-    remove_positions.shuffle(&mut thread_rng());
+    let mut remove_positions = all_cells().collect::<Vec<_>>();
+    remove_positions.shuffle(rng);
 
-    for position in remove_positions.into_iter().take(removal_count) {
-        let (row, col) = position;
+    let mut removed = 0;
+    for (row, col) in remove_positions {
+        if removed == removal_count {
+            break;
+        }
+        let num = board[row][col];
         board[row][col] = 0;
+        if has_unique_solution(&board) {
+            removed += 1;
+        } else {
+            board[row][col] = num;
+        }
     }
 
-    board
+    (board, removed)
+}
+
+/// returns true if the given `board` has exactly one solution
+pub fn has_unique_solution(board: &Board) -> bool {
+    count_solutions(board, 2).0 == SolutionCount::One
+}
+
+/// how many solutions a sudoku `Board` has, see `count_solutions()`
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum SolutionCount {
+    Zero,
+    One,
+    Many,
+}
+
+/// counts the solutions of a sudoku `Board` without modifying it.
+/// the search stops once `cap` solutions have been found, so at
+/// most `cap` solutions are returned alongside the `SolutionCount`.
+///
+/// NOTE: the search looks for at least 2 solutions, since a single found
+///       solution can not tell apart unique from ambiguous sudokus.
+pub fn count_solutions(board: &Board, cap: usize) -> (SolutionCount, Vec<Board>) {
+    let mut search = SolutionCounter::new(board, cap.max(2));
+    search.run();
+
+    let count = match search.solutions.len() {
+        0 => SolutionCount::Zero,
+        1 => SolutionCount::One,
+        _ => SolutionCount::Many,
+    };
+    search.solutions.truncate(cap);
+    (count, search.solutions)
+}
+
+/// backtracking search collecting the solutions of a board.
+/// keeps bitmasks of the values used in each row, column and block,
+/// and always branches on the cell with the fewest candidates.
+struct SolutionCounter {
+    board: Board,
+    rows: [u16; 9],
+    cols: [u16; 9],
+    blocks: [u16; 9],
+    limit: usize,
+    solutions: Vec<Board>,
+    /// set if the initial board contains the same value twice in a unit
+    contradiction: bool,
+}
+
+impl SolutionCounter {
+    /// returns a new `SolutionCounter` that will stop
+    /// once `limit` solutions of `board` have been found
+    fn new(board: &Board, limit: usize) -> Self {
+        let mut search = Self {
+            board: *board,
+            rows: [0; 9],
+            cols: [0; 9],
+            blocks: [0; 9],
+            limit,
+            solutions: Vec::new(),
+            contradiction: false,
+        };
+        for (r, row) in board.iter().enumerate() {
+            for (c, &x) in row.iter().enumerate() {
+                if x != 0 {
+                    search.contradiction |= search.candidates(r, c) & (1 << x) == 0;
+                    search.toggle(r, c, x);
+                }
+            }
+        }
+        search
+    }
+
+    /// flips the bit of value `x` in the masks of the cell at `r`, `c`
+    fn toggle(&mut self, r: usize, c: usize, x: u8) {
+        self.rows[r] ^= 1 << x;
+        self.cols[c] ^= 1 << x;
+        self.blocks[r / 3 * 3 + c / 3] ^= 1 << x;
+    }
+
+    /// returns a bitmask of the values that can be placed in the cell
+    /// at `r`, `c`. bit `x` is set if value `x` is possible.
+    fn candidates(&self, r: usize, c: usize) -> u16 {
+        !(self.rows[r] | self.cols[c] | self.blocks[r / 3 * 3 + c / 3]) & 0b11_1111_1110
+    }
+
+    fn run(&mut self) {
+        if self.contradiction {
+            return;
+        }
+
+        let mut best: Option<(usize, usize, u16)> = None;
+
+        for r in 0..9 {
+            for c in 0..9 {
+                if self.board[r][c] != 0 {
+                    continue;
+                }
+                let candidates = self.candidates(r, c);
+                if candidates == 0 {
+                    return; // dead end
+                }
+                if best.is_none_or(|(_, _, m)| candidates.count_ones() < m.count_ones()) {
+                    best = Some((r, c, candidates));
+                }
+            }
+        }
+
+        let Some((r, c, candidates)) = best else {
+            self.solutions.push(self.board); // no empty cells left
+            return;
+        };
+
+        for x in 1..=9 {
+            if candidates & (1 << x) != 0 {
+                self.board[r][c] = x;
+                self.toggle(r, c, x);
+                self.run();
+                self.toggle(r, c, x);
+                self.board[r][c] = 0;
+                if self.solutions.len() >= self.limit {
+                    return;
+                }
+            }
+        }
+    }
 }
 
 /// solves a sudoku, randomizing which empty cell of equal
-/// mrv to choose next.
+/// mrv to choose next using `rng`.
 /// this is used to generate random, fully solvable sudokus.
 ///
 /// NOTE: this does not guarantee a single-solution sudoku.
+///       use `count_solutions()` to check for that.
 ///
 /// TODO: currently empty cells needs to be recreated
 ///       on each recursive call, and are randomized anew
 ///       each time, which is an unnecessary overhead.
-pub fn solve_random(board: &mut Board) -> Result<(), ()> {
+pub fn solve_random<R: Rng>(board: &mut Board, rng: &mut R) -> Result<(), ()> {
     let mut empty_cells: Vec<(usize, usize)> = Vec::new();
 
     let mut rows: Vec<usize> = (0..9).collect::<Vec<_>>();
     let mut cols: Vec<usize> = (0..9).collect::<Vec<_>>();
-    rows.shuffle(&mut thread_rng());
-    cols.shuffle(&mut thread_rng());
+    rows.shuffle(rng);
+    cols.shuffle(rng);
 
     for &r in &rows {
         for &c in &cols {
@@ -138,7 +380,7 @@ pub fn solve_random(board: &mut Board) -> Result<(), ()> {
 
     for x in values {
         board[r][c] = x;
-        match solve_random(board) {
+        match solve_random(board, rng) {
             Err(_) => board[r][c] = 0, // reset value after backtrack
             _ => return Ok(()),        // done
         }
@@ -0,0 +1,643 @@
+use crate::logic::Technique::*;
+use crate::sudoku::{has_unique_solution, Board, Difficulty, Unit};
+
+use std::fmt;
+
+/// candidate bitmasks for every cell of a `Board`.
+/// bit `x` of a cell is set if the value `x` may still be placed there.
+/// filled cells have no candidates.
+pub type Candidates = [[u16; 9]; 9];
+
+/// solving techniques used by the logical solver,
+/// ordered from easiest to hardest.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+pub enum Technique {
+    NakedSingle,
+    HiddenSingle,
+    Pointing,
+    BoxLineReduction,
+    NakedPair,
+    HiddenPair,
+    NakedTriple,
+    HiddenTriple,
+    XWing,
+    XyWing,
+    Swordfish,
+    SimpleColoring,
+    XyChain,
+}
+
+impl Technique {
+    /// the `Difficulty` of a sudoku whose hardest required technique is this one
+    pub fn difficulty(&self) -> Difficulty {
+        match self {
+            NakedSingle | HiddenSingle => Difficulty::Easy,
+            Pointing | BoxLineReduction | NakedPair | HiddenPair => Difficulty::Mid,
+            NakedTriple | HiddenTriple | XWing | XyWing => Difficulty::Hard,
+            Swordfish | SimpleColoring | XyChain => Difficulty::Expert,
+        }
+    }
+}
+
+impl fmt::Display for Technique {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            NakedSingle => "Naked Single",
+            HiddenSingle => "Hidden Single",
+            Pointing => "Pointing",
+            BoxLineReduction => "Box/Line Reduction",
+            NakedPair => "Naked Pair",
+            HiddenPair => "Hidden Pair",
+            NakedTriple => "Naked Triple",
+            HiddenTriple => "Hidden Triple",
+            XWing => "X-Wing",
+            XyWing => "XY-Wing",
+            Swordfish => "Swordfish",
+            SimpleColoring => "Simple Coloring",
+            XyChain => "XY-Chain",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// a single deduction of the logical solver
+#[derive(Debug, PartialEq, Clone)]
+pub struct Step {
+    pub technique: Technique,
+    /// the cells forming the pattern the deduction is based on
+    pub cells: Vec<(usize, usize)>,
+    /// `(row, col, num)` of numbers that can be placed
+    pub placements: Vec<(usize, usize, u8)>,
+    /// `(row, col, num)` of candidates that can be removed
+    pub eliminations: Vec<(usize, usize, u8)>,
+}
+
+impl fmt::Display for Step {
+    /// formats a step like `Naked Pair r1c1 r1c5: r1c3-2 r1c8-7`,
+    /// using 1-based row and column numbers.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.technique)?;
+        for &(r, c) in &self.cells {
+            write!(f, " r{}c{}", r + 1, c + 1)?;
+        }
+        write!(f, ":")?;
+        for &(r, c, x) in &self.placements {
+            write!(f, " r{}c{}={}", r + 1, c + 1, x)?;
+        }
+        for &(r, c, x) in &self.eliminations {
+            write!(f, " r{}c{}-{}", r + 1, c + 1, x)?;
+        }
+        Ok(())
+    }
+}
+
+/// returns the candidates of every empty cell of `board`,
+/// only considering the numbers already placed.
+pub fn candidates(board: &Board) -> Candidates {
+    let mut candidates = [[0u16; 9]; 9];
+    for (r, c) in all_cells() {
+        if board[r][c] != 0 {
+            continue;
+        }
+        let mut used = 0u16;
+        for i in 0..9 {
+            used |= 1 << board[r][i];
+            used |= 1 << board[i][c];
+            used |= 1 << board[r / 3 * 3 + i / 3][c / 3 * 3 + i % 3];
+        }
+        candidates[r][c] = !used & 0b11_1111_1110;
+    }
+    candidates
+}
+
+/// finds the next deduction, trying the easiest techniques first.
+/// returns `None` if no technique applies.
+pub fn next_step(candidates: &Candidates) -> Option<Step> {
+    naked_single(candidates)
+        .or_else(|| hidden_single(candidates))
+        .or_else(|| pointing(candidates))
+        .or_else(|| box_line_reduction(candidates))
+        .or_else(|| naked_subset(candidates, 2))
+        .or_else(|| hidden_subset(candidates, 2))
+        .or_else(|| naked_subset(candidates, 3))
+        .or_else(|| hidden_subset(candidates, 3))
+        .or_else(|| fish(candidates, 2))
+        .or_else(|| xy_wing(candidates))
+        .or_else(|| fish(candidates, 3))
+        .or_else(|| simple_coloring(candidates))
+        .or_else(|| xy_chain(candidates))
+}
+
+/// places the numbers and removes the candidates of `step`.
+/// placed numbers are also removed from the candidates of their row, column and block.
+pub fn apply_step(board: &mut Board, candidates: &mut Candidates, step: &Step) {
+    for &(r, c, x) in &step.placements {
+        board[r][c] = x;
+        candidates[r][c] = 0;
+        for (pr, pc) in all_cells().filter(|&peer| sees(peer, (r, c))) {
+            candidates[pr][pc] &= !(1 << x);
+        }
+    }
+    for &(r, c, x) in &step.eliminations {
+        candidates[r][c] &= !(1 << x);
+    }
+}
+
+/// returns the hardest technique the logical solver needs to solve `board`.
+/// returns `None` if the board does not have a unique solution
+/// or can not be solved without guessing.
+pub fn hardest_technique(board: &Board) -> Option<Technique> {
+    if !has_unique_solution(board) {
+        return None;
+    }
+
+    let mut board = *board;
+    let mut candidates = candidates(&board);
+    let mut hardest = NakedSingle;
+
+    while board.iter().flatten().any(|&cell| cell == 0) {
+        let step = next_step(&candidates)?;
+        hardest = hardest.max(step.technique);
+        apply_step(&mut board, &mut candidates, &step);
+    }
+
+    Some(hardest)
+}
+
+/// grades a sudoku by the hardest technique needed to solve it.
+/// see `hardest_technique()` and `Technique::difficulty()`.
+pub fn grade(board: &Board) -> Option<Difficulty> {
+    hardest_technique(board).map(|technique| technique.difficulty())
+}
+
+/// a cell with only one candidate left
+fn naked_single(candidates: &Candidates) -> Option<Step> {
+    let (r, c) = all_cells().find(|&(r, c)| candidates[r][c].count_ones() == 1)?;
+    let x = digits(candidates[r][c]).next()?;
+    Some(Step {
+        technique: NakedSingle,
+        cells: vec![(r, c)],
+        placements: vec![(r, c, x)],
+        eliminations: vec![],
+    })
+}
+
+/// a number that has only one possible cell left in a row, column or block
+fn hidden_single(candidates: &Candidates) -> Option<Step> {
+    for u in 0..27 {
+        for x in 1..=9 {
+            let positions = with_candidate(candidates, &unit(u), x);
+            if let [(r, c)] = positions[..] {
+                return Some(Step {
+                    technique: HiddenSingle,
+                    cells: vec![(r, c)],
+                    placements: vec![(r, c, x)],
+                    eliminations: vec![],
+                });
+            }
+        }
+    }
+    None
+}
+
+/// all candidates of a number in a block lie on one row or column,
+/// so the number can be removed from the rest of that line.
+fn pointing(candidates: &Candidates) -> Option<Step> {
+    for b in 0..9 {
+        for x in 1..=9 {
+            let positions = with_candidate(candidates, &unit(18 + b), x);
+            if positions.len() < 2 {
+                continue;
+            }
+            let (r, c) = positions[0];
+            let lines = [
+                (positions.iter().all(|p| p.0 == r), unit(r)),
+                (positions.iter().all(|p| p.1 == c), unit(9 + c)),
+            ];
+            for (aligned, line) in lines {
+                if !aligned {
+                    continue;
+                }
+                let outside = line.into_iter().filter(|&cell| block_of(cell) != b);
+                let eliminations = eliminate(candidates, outside, x);
+                if !eliminations.is_empty() {
+                    return Some(Step {
+                        technique: Pointing,
+                        cells: positions,
+                        placements: vec![],
+                        eliminations,
+                    });
+                }
+            }
+        }
+    }
+    None
+}
+
+/// all candidates of a number in a row or column lie in one block,
+/// so the number can be removed from the rest of that block.
+fn box_line_reduction(candidates: &Candidates) -> Option<Step> {
+    for u in 0..18 {
+        let line = unit(u);
+        for x in 1..=9 {
+            let positions = with_candidate(candidates, &line, x);
+            if positions.len() < 2 {
+                continue;
+            }
+            let b = block_of(positions[0]);
+            if positions.iter().any(|&p| block_of(p) != b) {
+                continue;
+            }
+            let outside = unit(18 + b).into_iter().filter(|cell| !line.contains(cell));
+            let eliminations = eliminate(candidates, outside, x);
+            if !eliminations.is_empty() {
+                return Some(Step {
+                    technique: BoxLineReduction,
+                    cells: positions,
+                    placements: vec![],
+                    eliminations,
+                });
+            }
+        }
+    }
+    None
+}
+
+/// `n` cells of a unit that together have only `n` candidates,
+/// so these can be removed from the other cells of the unit.
+fn naked_subset(candidates: &Candidates, n: usize) -> Option<Step> {
+    let technique = if n == 2 { NakedPair } else { NakedTriple };
+
+    for u in 0..27 {
+        let cells = unit(u);
+        let pool = cells
+            .into_iter()
+            .filter(|&(r, c)| (2..=n as u32).contains(&candidates[r][c].count_ones()))
+            .collect::<Vec<_>>();
+
+        for subset in combinations(&pool, n) {
+            let mask = subset.iter().fold(0, |m, &(r, c)| m | candidates[r][c]);
+            if mask.count_ones() as usize != n {
+                continue;
+            }
+            let mut eliminations = Vec::new();
+            for &(r, c) in cells.iter().filter(|cell| !subset.contains(cell)) {
+                for x in digits(candidates[r][c] & mask) {
+                    eliminations.push((r, c, x));
+                }
+            }
+            if !eliminations.is_empty() {
+                return Some(Step {
+                    technique,
+                    cells: subset,
+                    placements: vec![],
+                    eliminations,
+                });
+            }
+        }
+    }
+    None
+}
+
+/// `n` numbers that can only go into the same `n` cells of a unit,
+/// so all other candidates can be removed from these cells.
+fn hidden_subset(candidates: &Candidates, n: usize) -> Option<Step> {
+    let technique = if n == 2 { HiddenPair } else { HiddenTriple };
+
+    for u in 0..27 {
+        let cells = unit(u);
+        let positions = |x: u8| -> u16 {
+            (0..9)
+                .filter(|&i| candidates[cells[i].0][cells[i].1] & (1 << x) != 0)
+                .fold(0, |m, i| m | (1 << i))
+        };
+        let pool = (1..=9)
+            .filter(|&x| (1..=n as u32).contains(&positions(x).count_ones()))
+            .collect::<Vec<u8>>();
+
+        for subset in combinations(&pool, n) {
+            let spots = subset.iter().fold(0, |m, &x| m | positions(x));
+            if spots.count_ones() as usize != n {
+                continue;
+            }
+            let mask = subset.iter().fold(0u16, |m, &x| m | (1 << x));
+            let subset_cells = (0..9)
+                .filter(|&i| spots & (1 << i) != 0)
+                .map(|i| cells[i])
+                .collect::<Vec<_>>();
+
+            let mut eliminations = Vec::new();
+            for &(r, c) in &subset_cells {
+                for x in digits(candidates[r][c] & !mask) {
+                    eliminations.push((r, c, x));
+                }
+            }
+            if !eliminations.is_empty() {
+                return Some(Step {
+                    technique,
+                    cells: subset_cells,
+                    placements: vec![],
+                    eliminations,
+                });
+            }
+        }
+    }
+    None
+}
+
+/// X-Wing (`n = 2`) and Swordfish (`n = 3`):
+/// a number that is confined to the same `n` columns in `n` rows
+/// can be removed from the rest of these columns, and vice versa.
+fn fish(candidates: &Candidates, n: usize) -> Option<Step> {
+    let technique = if n == 2 { XWing } else { Swordfish };
+
+    for x in 1..=9 {
+        for by_rows in [true, false] {
+            let cell = |line: usize, i: usize| if by_rows { (line, i) } else { (i, line) };
+            let spots = |line: usize| -> u16 {
+                (0..9)
+                    .filter(|&i| {
+                        let (r, c) = cell(line, i);
+                        candidates[r][c] & (1 << x) != 0
+                    })
+                    .fold(0, |m, i| m | (1 << i))
+            };
+            let pool = (0..9)
+                .filter(|&line| (2..=n as u32).contains(&spots(line).count_ones()))
+                .collect::<Vec<_>>();
+
+            for lines in combinations(&pool, n) {
+                let covered = lines.iter().fold(0, |m, &line| m | spots(line));
+                if covered.count_ones() as usize != n {
+                    continue;
+                }
+                let outside = (0..9)
+                    .filter(|line| !lines.contains(line))
+                    .flat_map(|line| {
+                        (0..9)
+                            .filter(move |&i| covered & (1 << i) != 0)
+                            .map(move |i| cell(line, i))
+                    });
+                let eliminations = eliminate(candidates, outside, x);
+                if !eliminations.is_empty() {
+                    let cells = lines
+                        .iter()
+                        .flat_map(|&line| (0..9).map(move |i| cell(line, i)))
+                        .filter(|&(r, c)| candidates[r][c] & (1 << x) != 0)
+                        .collect();
+                    return Some(Step {
+                        technique,
+                        cells,
+                        placements: vec![],
+                        eliminations,
+                    });
+                }
+            }
+        }
+    }
+    None
+}
+
+/// a pivot cell with candidates `xy` sees two pincer cells with `xz` and `yz`.
+/// one of the pincers has to be `z`, so `z` can be removed from all cells seeing both.
+fn xy_wing(candidates: &Candidates) -> Option<Step> {
+    let bivalue = bivalue_cells(candidates);
+
+    for &pivot in &bivalue {
+        let pivot_mask = candidates[pivot.0][pivot.1];
+        for &first in bivalue.iter().filter(|&&cell| sees(cell, pivot)) {
+            let first_mask = candidates[first.0][first.1];
+            let shared = first_mask & pivot_mask;
+            if shared.count_ones() != 1 {
+                continue;
+            }
+            let z = first_mask & !pivot_mask;
+            let second_mask = (pivot_mask & !shared) | z;
+
+            for &second in bivalue.iter().filter(|&&cell| sees(cell, pivot)) {
+                if second == first || candidates[second.0][second.1] != second_mask {
+                    continue;
+                }
+                let z = digits(z).next()?;
+                let seeing_both =
+                    all_cells().filter(|&cell| sees(cell, first) && sees(cell, second));
+                let eliminations = eliminate(candidates, seeing_both, z);
+                if !eliminations.is_empty() {
+                    return Some(Step {
+                        technique: XyWing,
+                        cells: vec![pivot, first, second],
+                        placements: vec![],
+                        eliminations,
+                    });
+                }
+            }
+        }
+    }
+    None
+}
+
+/// colors chains of cells that are the only two candidates for a number in a unit
+/// with alternating colors, so exactly one of the colors is true.
+/// - if two cells of the same color see each other, that color is false.
+/// - cells seeing both colors can not contain the number.
+fn simple_coloring(candidates: &Candidates) -> Option<Step> {
+    for x in 1..=9 {
+        let mut links: Vec<Vec<(usize, usize)>> = vec![Vec::new(); 81];
+        for u in 0..27 {
+            if let [a, b] = with_candidate(candidates, &unit(u), x)[..] {
+                links[a.0 * 9 + a.1].push(b);
+                links[b.0 * 9 + b.1].push(a);
+            }
+        }
+
+        let mut colored = [false; 81];
+        for start in all_cells() {
+            if colored[start.0 * 9 + start.1] || links[start.0 * 9 + start.1].is_empty() {
+                continue;
+            }
+
+            // color the chain starting at `start` with alternating colors
+            let mut chain = vec![(start, false)];
+            colored[start.0 * 9 + start.1] = true;
+            let mut i = 0;
+            while i < chain.len() {
+                let (cell, color) = chain[i];
+                for &next in &links[cell.0 * 9 + cell.1] {
+                    if !colored[next.0 * 9 + next.1] {
+                        colored[next.0 * 9 + next.1] = true;
+                        chain.push((next, !color));
+                    }
+                }
+                i += 1;
+            }
+
+            let cells = chain.iter().map(|&(cell, _)| cell).collect::<Vec<_>>();
+
+            for color in [false, true] {
+                let same = chain
+                    .iter()
+                    .filter(|&&(_, c)| c == color)
+                    .map(|&(cell, _)| cell);
+                let contradiction = same.clone().any(|a| same.clone().any(|b| sees(a, b)));
+                if contradiction {
+                    return Some(Step {
+                        technique: SimpleColoring,
+                        cells,
+                        placements: vec![],
+                        eliminations: eliminate(candidates, same, x),
+                    });
+                }
+            }
+
+            let sees_color =
+                |cell, color| chain.iter().any(|&(c, col)| col == color && sees(cell, c));
+            let trapped = all_cells()
+                .filter(|cell| !cells.contains(cell))
+                .filter(|&cell| sees_color(cell, false) && sees_color(cell, true));
+            let eliminations = eliminate(candidates, trapped, x);
+            if !eliminations.is_empty() {
+                return Some(Step {
+                    technique: SimpleColoring,
+                    cells,
+                    placements: vec![],
+                    eliminations,
+                });
+            }
+        }
+    }
+    None
+}
+
+/// a chain of cells with two candidates each, where neighbouring cells
+/// see each other and share a candidate. if the chain starts and ends with the
+/// same number `z`, one of its ends has to be `z`, so `z` can be removed
+/// from all cells seeing both ends.
+fn xy_chain(candidates: &Candidates) -> Option<Step> {
+    const MAX_LENGTH: usize = 12;
+
+    /// extends `chain`, whose last cell has to be `out` if the first cell is not `z`
+    fn extend(
+        candidates: &Candidates,
+        bivalue: &[(usize, usize)],
+        chain: &mut Vec<(usize, usize)>,
+        out: u8,
+        z: u8,
+    ) -> Option<Step> {
+        let last = *chain.last()?;
+        if out == z && chain.len() >= 3 {
+            let first = chain[0];
+            let seeing_both = all_cells()
+                .filter(|cell| !chain.contains(cell))
+                .filter(|&cell| sees(cell, first) && sees(cell, last));
+            let eliminations = eliminate(candidates, seeing_both, z);
+            if !eliminations.is_empty() {
+                return Some(Step {
+                    technique: XyChain,
+                    cells: chain.clone(),
+                    placements: vec![],
+                    eliminations,
+                });
+            }
+        }
+        if chain.len() == MAX_LENGTH {
+            return None;
+        }
+
+        for &next in bivalue {
+            let mask = candidates[next.0][next.1];
+            if mask & (1 << out) == 0 || !sees(next, last) || chain.contains(&next) {
+                continue;
+            }
+            let next_out = digits(mask & !(1 << out)).next()?;
+            chain.push(next);
+            if let Some(step) = extend(candidates, bivalue, chain, next_out, z) {
+                return Some(step);
+            }
+            chain.pop();
+        }
+        None
+    }
+
+    let bivalue = bivalue_cells(candidates);
+    for &start in &bivalue {
+        for z in digits(candidates[start.0][start.1]) {
+            let out = digits(candidates[start.0][start.1] & !(1 << z)).next()?;
+            let mut chain = vec![start];
+            if let Some(step) = extend(candidates, &bivalue, &mut chain, out, z) {
+                return Some(step);
+            }
+        }
+    }
+    None
+}
+
+/// returns the cells of a row (`0..9`), column (`9..18`) or block (`18..27`)
+fn unit(i: usize) -> [(usize, usize); 9] {
+    match i / 9 {
+        0 => Unit::Row(i),
+        1 => Unit::Col(i - 9),
+        _ => Unit::Block(i - 18),
+    }
+    .cells()
+}
+
+/// returns the positions of all cells, row by row
+pub(crate) fn all_cells() -> impl Iterator<Item = (usize, usize)> + Clone {
+    (0..81).map(|i| (i / 9, i % 9))
+}
+
+fn block_of((r, c): (usize, usize)) -> usize {
+    r / 3 * 3 + c / 3
+}
+
+/// returns true if two different cells share a row, column or block
+fn sees(a: (usize, usize), b: (usize, usize)) -> bool {
+    a != b && (a.0 == b.0 || a.1 == b.1 || block_of(a) == block_of(b))
+}
+
+/// returns the values set in a candidate bitmask
+fn digits(mask: u16) -> impl Iterator<Item = u8> {
+    (1..=9).filter(move |x| mask & (1 << x) != 0)
+}
+
+fn bivalue_cells(candidates: &Candidates) -> Vec<(usize, usize)> {
+    all_cells()
+        .filter(|&(r, c)| candidates[r][c].count_ones() == 2)
+        .collect()
+}
+
+/// returns the `cells` that have `x` as a candidate
+fn with_candidate(candidates: &Candidates, cells: &[(usize, usize)], x: u8) -> Vec<(usize, usize)> {
+    cells
+        .iter()
+        .copied()
+        .filter(|&(r, c)| candidates[r][c] & (1 << x) != 0)
+        .collect()
+}
+
+/// returns the eliminations of `x` from those of `cells` that have it as a candidate
+fn eliminate(
+    candidates: &Candidates,
+    cells: impl Iterator<Item = (usize, usize)>,
+    x: u8,
+) -> Vec<(usize, usize, u8)> {
+    cells
+        .filter(|&(r, c)| candidates[r][c] & (1 << x) != 0)
+        .map(|(r, c)| (r, c, x))
+        .collect()
+}
+
+/// returns all subsets of `items` with `n` elements
+fn combinations<T: Copy>(items: &[T], n: usize) -> Vec<Vec<T>> {
+    if n == 0 {
+        return vec![vec![]];
+    }
+    let mut subsets = Vec::new();
+    for (i, &item) in items.iter().enumerate() {
+        for mut rest in combinations(&items[i + 1..], n - 1) {
+            rest.insert(0, item);
+            subsets.push(rest);
+        }
+    }
+    subsets
+}
@@ -1,7 +1,11 @@
+pub mod format;
 pub mod generator;
+pub mod logic;
 pub mod validator;
 
+pub use format::*;
 pub use generator::*;
+pub use logic::*;
 pub use validator::*;
 
 pub type Board = [[u8; 9]; 9];
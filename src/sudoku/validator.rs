@@ -1,4 +1,6 @@
-use crate::sudoku::Board;
+use crate::sudoku::{count_solutions, Board, SolutionCount};
+
+use std::fmt;
 
 /// returns true if the provided sudoku `Board` is in a solved state
 pub fn is_solution(sudoku: &Board) -> bool {
@@ -31,3 +33,103 @@ pub fn is_solution(sudoku: &Board) -> bool {
 
     true
 }
+
+/// a row, column or block of a sudoku `Board`, indexed from 0
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Unit {
+    Row(usize),
+    Col(usize),
+    Block(usize),
+}
+
+impl Unit {
+    /// returns the positions of the cells in this unit
+    pub fn cells(&self) -> [(usize, usize); 9] {
+        let mut cells = [(0, 0); 9];
+        for (i, cell) in cells.iter_mut().enumerate() {
+            *cell = match *self {
+                Unit::Row(r) => (r, i),
+                Unit::Col(c) => (i, c),
+                Unit::Block(b) => (b / 3 * 3 + i / 3, b % 3 * 3 + i % 3),
+            };
+        }
+        cells
+    }
+}
+
+impl fmt::Display for Unit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Unit::Row(r) => write!(f, "row {}", r + 1),
+            Unit::Col(c) => write!(f, "column {}", c + 1),
+            Unit::Block(b) => write!(f, "block {}", b + 1),
+        }
+    }
+}
+
+/// a number occurring more than once in a unit
+#[derive(Debug, PartialEq, Clone)]
+pub struct Conflict {
+    pub unit: Unit,
+    pub num: u8,
+    pub cells: Vec<(usize, usize)>,
+}
+
+impl fmt::Display for Conflict {
+    /// formats a conflict like `row 1 contains 3 at r1c1, r1c3`,
+    /// using 1-based row and column numbers.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let cells = self
+            .cells
+            .iter()
+            .map(|(r, c)| format!("r{}c{}", r + 1, c + 1))
+            .collect::<Vec<_>>();
+        write!(
+            f,
+            "{} contains {} at {}",
+            self.unit,
+            self.num,
+            cells.join(", ")
+        )
+    }
+}
+
+/// returns every number that occurs more than once in a row, column or block
+/// of a possibly unfinished sudoku `Board`.
+pub fn find_conflicts(board: &Board) -> Vec<Conflict> {
+    let units = (0..9)
+        .map(Unit::Row)
+        .chain((0..9).map(Unit::Col))
+        .chain((0..9).map(Unit::Block));
+
+    let mut conflicts = Vec::new();
+    for unit in units {
+        for num in 1..=9 {
+            let cells = unit
+                .cells()
+                .into_iter()
+                .filter(|&(r, c)| board[r][c] == num)
+                .collect::<Vec<_>>();
+            if cells.len() > 1 {
+                conflicts.push(Conflict { unit, num, cells });
+            }
+        }
+    }
+    conflicts
+}
+
+/// the result of checking a possibly unfinished sudoku with `validate()`
+#[derive(Debug, PartialEq, Clone)]
+pub struct Validation {
+    pub conflicts: Vec<Conflict>,
+    pub solutions: SolutionCount,
+}
+
+/// checks a possibly unfinished sudoku `Board` for conflicts
+/// and whether it can still be solved in exactly one way.
+pub fn validate(board: &Board) -> Validation {
+    Validation {
+        conflicts: find_conflicts(board),
+        solutions: count_solutions(board, 2).0,
+    }
+}
@@ -0,0 +1,184 @@
+use crate::{config, keys::*, state::Mode, theme::*};
+
+#[test]
+fn empty_config_is_vim() {
+    let keymap = config::parse("# nothing here\n").unwrap().keymap;
+    assert_eq!(keymap, Keymap::vim());
+    assert_eq!(
+        keymap.action(Key::Char('h'), Mode::Edit),
+        Some(Action::MoveLeft)
+    );
+    assert_eq!(
+        keymap.action(Key::Esc, Mode::Markup),
+        Some(Action::EditMode)
+    );
+    assert_eq!(keymap.action(Key::Left, Mode::Edit), None);
+}
+
+#[test]
+fn mode_bindings_take_precedence() {
+    let keymap = Keymap::vim();
+    assert_eq!(
+        keymap.action(Key::Char('c'), Mode::Edit),
+        Some(Action::ToggleConflicts)
+    );
+    assert_eq!(
+        keymap.action(Key::Char('c'), Mode::Visual),
+        Some(Action::PaintSelection)
+    );
+    assert_eq!(keymap.action(Key::Char('b'), Mode::Edit), None);
+    assert_eq!(
+        keymap.action(Key::Char('b'), Mode::Go),
+        Some(Action::ListBranches)
+    );
+}
+
+#[test]
+fn presets() {
+    let arrows = Keymap::preset("arrows").unwrap();
+    assert_eq!(arrows.action(Key::Up, Mode::Edit), Some(Action::MoveUp));
+    assert_eq!(
+        arrows.action(Key::Char('k'), Mode::Edit),
+        Some(Action::MoveUp)
+    );
+    assert_eq!(
+        arrows.action(Key::PageDown, Mode::Edit),
+        Some(Action::MoveFarDown)
+    );
+
+    let wasd = Keymap::preset("wasd").unwrap();
+    assert_eq!(
+        wasd.action(Key::Char('a'), Mode::Edit),
+        Some(Action::MoveLeft)
+    );
+    assert_eq!(
+        wasd.action(Key::Char('s'), Mode::Visual),
+        Some(Action::MoveDown)
+    );
+    assert_eq!(
+        wasd.action(Key::Char('t'), Mode::Visual),
+        Some(Action::StampSelection)
+    );
+    assert_eq!(
+        wasd.action(Key::Char('n'), Mode::Edit),
+        Some(Action::MarkupMode)
+    );
+    assert_eq!(
+        wasd.action(Key::Char('o'), Mode::Edit),
+        Some(Action::Export)
+    );
+
+    assert!(Keymap::preset("emacs").is_err());
+}
+
+#[test]
+fn bindings_replace_the_preset_keys() {
+    let text = r##"
+        preset = "arrows" # start from the arrow keys
+        [keys]
+        move_left = ["Left", "#"]
+        hint = "Tab"
+        undo = []
+        [keys.visual]
+        paint_selection = "p"
+    "##;
+    let keymap = config::parse(text).unwrap().keymap;
+    assert_eq!(
+        keymap.action(Key::Char('#'), Mode::Edit),
+        Some(Action::MoveLeft)
+    );
+    assert_eq!(keymap.action(Key::Char('h'), Mode::Edit), None);
+    assert_eq!(keymap.action(Key::Tab, Mode::Edit), Some(Action::Hint));
+    assert_eq!(keymap.action(Key::Char('?'), Mode::Edit), None);
+    assert_eq!(keymap.action(Key::Char('u'), Mode::Edit), None);
+    assert_eq!(
+        keymap.action(Key::Char('p'), Mode::Visual),
+        Some(Action::PaintSelection)
+    );
+    assert_eq!(
+        keymap.action(Key::Char('c'), Mode::Visual),
+        Some(Action::ToggleConflicts)
+    );
+}
+
+#[test]
+fn invalid_configs_are_rejected() {
+    for text in [
+        "preset = \"emacs\"",
+        "colors = \"red\"",
+        "[keys]\nfly = \"f\"",
+        "[keys]\nhint = \"F1\"",
+        "[keys]\nhint = \"?",
+        "[keys]\nhint = [\"?\" \"!\"]",
+        "[keys.edit]",
+        "[keys]\nhint",
+        "[keys]\nhint = \"5\"",
+        "[keys.visual]\nundo = [\"u\", \"1\"]",
+        "[keys.visual]\nmark_selection = \"m\"",
+    ] {
+        assert!(config::parse(text).is_err(), "{text}");
+    }
+    assert!(config::parse("[keys]\nhint = \"F1\"")
+        .unwrap_err()
+        .starts_with("line 2"));
+    assert!(
+        config::parse("\n[keys]\nmove_left = [\n  \"h\",\n  \"1\",\n]")
+            .unwrap_err()
+            .starts_with("line 5")
+    );
+}
+
+#[test]
+fn full_toml_syntax_is_accepted() {
+    let text = r#"
+        [keys]
+        hint = 'Tab'
+        go.list_branches = "B"
+        move_left = [
+            "Left",
+            'h', # a comment inside the list
+        ]
+    "#;
+    let keymap = config::parse(text).unwrap().keymap;
+    assert_eq!(keymap.action(Key::Tab, Mode::Edit), Some(Action::Hint));
+    assert_eq!(keymap.action(Key::Left, Mode::Edit), Some(Action::MoveLeft));
+    assert_eq!(
+        keymap.action(Key::Char('h'), Mode::Edit),
+        Some(Action::MoveLeft)
+    );
+    assert_eq!(
+        keymap.action(Key::Char('B'), Mode::Go),
+        Some(Action::ListBranches)
+    );
+
+    let error = config::parse(
+        "[keys]
+move_left = [
+  \"Left\",
+  \"F1\",
+]",
+    )
+    .unwrap_err();
+    assert!(error.starts_with("line 4"), "{error}");
+}
+
+#[test]
+fn themes_are_read_and_saved() {
+    let config = config::parse("theme = \"colorblind\"\n").unwrap();
+    assert_eq!(config.theme, Theme::by_name("colorblind").unwrap());
+    assert_eq!(config::parse("").unwrap().theme, Theme::default());
+    assert!(config::parse("theme = \"neon\"").is_err());
+
+    let light = Theme::by_name("light").unwrap();
+    assert_eq!(config::with_theme("", &light), "theme = \"light\"\n");
+
+    // the value is replaced before the first section, and added otherwise
+    let text = "preset = \"wasd\"\ntheme = \"default\" # old\n[keys]\ntheme = \"x\"\n";
+    let saved = config::with_theme(text, &light);
+    assert_eq!(
+        saved,
+        "preset = \"wasd\"\ntheme = \"light\" # old\n[keys]\ntheme = \"x\"\n"
+    );
+    let saved = config::with_theme("[keys]\nhint = \"?\"\n", &light);
+    assert_eq!(config::parse(&saved).unwrap().theme, light);
+}
@@ -0,0 +1,53 @@
+use crate::{ex::*, state::*, sudoku::*};
+
+#[test]
+fn parse_commands() {
+    assert_eq!(parse("new"), Ok(ExCommand::New(None)));
+    assert_eq!(
+        parse("new hard"),
+        Ok(ExCommand::New(Some(Difficulty::Hard)))
+    );
+    assert_eq!(
+        parse(" w  game.save "),
+        Ok(ExCommand::Write(Some("game.save".into())))
+    );
+    assert_eq!(
+        parse("e game.save"),
+        Ok(ExCommand::Edit("game.save".into()))
+    );
+    assert_eq!(parse("seed 42"), Ok(ExCommand::Seed(Some(42))));
+    assert_eq!(parse("hint"), Ok(ExCommand::Hint));
+    assert_eq!(parse("check"), Ok(ExCommand::Check));
+    assert_eq!(parse("theme"), Ok(ExCommand::Theme(None)));
+    assert_eq!(
+        parse("theme light"),
+        Ok(ExCommand::Theme(Some("light".into())))
+    );
+    assert_eq!(parse("q"), Ok(ExCommand::Quit { save: true }));
+    assert_eq!(parse("q!"), Ok(ExCommand::Quit { save: false }));
+
+    assert!(parse("").is_err());
+    assert!(parse("e").is_err());
+    assert!(parse("new impossible").is_err());
+    assert!(parse("seed -1").is_err());
+    assert!(parse("hint 3").is_err());
+    assert!(parse("w a b").is_err());
+    assert!(parse("frobnicate").is_err());
+}
+
+#[test]
+fn seed_command_replays_games() {
+    let mut state = State::new_game(Difficulty::Easy, 1, false);
+    state.highlight_conflicts = false;
+
+    run(ExCommand::Seed(Some(99)), &mut state, false).unwrap();
+    assert_eq!(state.seed, Some(99));
+    assert_eq!(state.board, generate_sudoku(99, Difficulty::Easy));
+    assert!(!state.highlight_conflicts);
+
+    run(ExCommand::Seed(None), &mut state, false).unwrap();
+    assert_eq!(
+        state.message,
+        "Seed: 99, replay with `shdoku -d easy -s 99`"
+    );
+}
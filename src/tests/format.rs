@@ -0,0 +1,64 @@
+use crate::format::*;
+
+#[test]
+fn parse_valid() {
+    let line = "003020600900305001001806400008102900700000008006708200002609500800203009005010300";
+    let board = parse_board(line).expect("valid puzzle rejected");
+    assert_eq!(board[0], [0, 0, 3, 0, 2, 0, 6, 0, 0]);
+    assert_eq!(board[8], [0, 0, 5, 0, 1, 0, 3, 0, 0]);
+
+    let dotted = line.replace('0', ".");
+    assert_eq!(parse_board(&format!("  {dotted}\n")), Ok(board));
+}
+
+#[test]
+fn parse_invalid() {
+    let line = "003020600900305001001806400008102900700000008006708200002609500800203009005010300";
+
+    assert!(parse_board(&line[1..]).unwrap_err().contains("81 cells"));
+    assert!(parse_board(&line.replacen('0', "x", 1))
+        .unwrap_err()
+        .contains("'x' at position 1"));
+
+    let duplicate = line.replacen('0', "3", 1);
+    assert!(parse_board(&duplicate)
+        .unwrap_err()
+        .contains("row 1 contains 3 at r1c1, r1c3"));
+
+    let unsolvable = format!("12345678.........9{}", ".".repeat(63));
+    assert!(parse_board(&unsolvable)
+        .unwrap_err()
+        .contains("no solution"));
+}
+
+#[test]
+fn format_roundtrip() {
+    let line = "..3.2.6..9..3.5..1..18.64....81.29..7.......8..67.82....26.95..8..2.3..9..5.1.3..";
+    let board = parse_board(line).unwrap();
+    assert_eq!(format_line(&board), line);
+
+    let grid = format_grid(&board);
+    let lines = grid.lines().collect::<Vec<_>>();
+    assert_eq!(lines.len(), 13);
+    assert_eq!(lines[0], "┌────────┬────────┬────────┐");
+    assert_eq!(lines[1], "│     3  │   2    │ 6      │");
+    assert_eq!(lines[4], "├────────┼────────┼────────┤");
+    assert_eq!(lines[12], "└────────┴────────┴────────┘");
+}
+
+#[test]
+fn format_marks_of_empty_cells() {
+    let mut board = [[0; 9]; 9];
+    let mut markups = [[[false; 9]; 9]; 9];
+    markups[0][0][0] = true;
+    markups[0][0][4] = true;
+    markups[0][1][2] = true;
+    board[0][1] = 7;
+
+    let marks = format_marks(&board, &markups);
+    assert!(marks
+        .lines()
+        .nth(1)
+        .unwrap()
+        .starts_with("│ 15                            │"));
+}
@@ -1,4 +1,4 @@
-use crate::{generator::*, Board};
+use crate::{generator::*, rand::thread_rng, Board};
 
 #[test]
 fn generated_sudoku_uniqueness() {
@@ -11,7 +11,7 @@ fn generated_sudoku_uniqueness() {
 
     for i in 0..iterations {
         let inb4 = Instant::now();
-        uniques.insert(generate_sudoku(Difficulty::Custom(0)));
+        uniques.insert(generate_sudoku(i as u64, Difficulty::Custom(0)));
         let dt = Instant::now() - inb4;
         total_time += dt;
         println!("iteration {: >5} took {:.5} s", i, dt.as_secs_f64());
@@ -40,8 +40,8 @@ fn generated_sudoku_solvability_small() {
 
     for difficulty in [Easy, Mid, Hard, Expert] {
         for _ in 0..iterations {
-            let mut sudoku = generate_sudoku(difficulty);
-            solve_random(&mut sudoku).expect("Generated sudoku not solvable");
+            let mut sudoku = generate_sudoku(random_seed(), difficulty);
+            solve_random(&mut sudoku, &mut thread_rng()).expect("Generated sudoku not solvable");
         }
     }
 }
@@ -55,8 +55,115 @@ fn generated_sudoku_solvability_large() {
 
     for difficulty in [Easy, Mid, Hard, Expert] {
         for _ in 0..iterations {
-            let mut sudoku = generate_sudoku(difficulty);
-            solve_random(&mut sudoku).expect("Generated sudoku not solvable");
+            let mut sudoku = generate_sudoku(random_seed(), difficulty);
+            solve_random(&mut sudoku, &mut thread_rng()).expect("Generated sudoku not solvable");
         }
     }
 }
+
+#[test]
+fn generated_sudoku_single_solution() {
+    use Difficulty::*;
+
+    let iterations = 5;
+
+    for difficulty in [Easy, Mid, Hard, Expert] {
+        for _ in 0..iterations {
+            let sudoku = generate_sudoku(random_seed(), difficulty);
+            assert!(
+                has_unique_solution(&sudoku),
+                "Generated {} sudoku has more than one solution",
+                difficulty
+            );
+        }
+    }
+}
+
+#[test]
+fn count_solutions_categories() {
+    let solved = [
+        [7, 6, 9, 5, 3, 8, 1, 2, 4],
+        [2, 4, 3, 7, 1, 9, 6, 5, 8],
+        [8, 5, 1, 4, 6, 2, 9, 7, 3],
+        [4, 8, 6, 9, 7, 5, 3, 1, 2],
+        [5, 3, 7, 6, 2, 1, 4, 8, 9],
+        [1, 9, 2, 8, 4, 3, 7, 6, 5],
+        [6, 1, 8, 3, 5, 4, 2, 9, 7],
+        [9, 7, 4, 2, 8, 6, 5, 3, 1],
+        [3, 2, 5, 1, 9, 7, 8, 4, 6],
+    ];
+
+    let (count, solutions) = count_solutions(&solved, 2);
+    assert_eq!(count, SolutionCount::One);
+    assert_eq!(solutions, vec![solved]);
+
+    // a second 6 in the third column
+    let mut broken = solved;
+    broken[0][0] = 0;
+    broken[0][1] = 0;
+    broken[0][2] = 6;
+    let (count, solutions) = count_solutions(&broken, 2);
+    assert_eq!(count, SolutionCount::Zero);
+    assert!(solutions.is_empty());
+
+    let mut wrong = solved;
+    wrong[8][8] = 9;
+    assert_eq!(count_solutions(&wrong, 2).0, SolutionCount::Zero);
+
+    // the 9s and 1s in rows 1 and 3 can be swapped
+    let mut ambiguous = solved;
+    for (r, c) in [(0, 2), (0, 6), (2, 2), (2, 6)] {
+        ambiguous[r][c] = 0;
+    }
+    let (count, solutions) = count_solutions(&ambiguous, 5);
+    assert_eq!(count, SolutionCount::Many);
+    assert_eq!(solutions.len(), 2);
+    assert!(solutions.contains(&solved));
+
+    let (count, solutions) = count_solutions(&ambiguous, 1);
+    assert_eq!(count, SolutionCount::Many);
+    assert_eq!(solutions.len(), 1);
+
+    let (count, solutions) = count_solutions(&[[0; 9]; 9], 3);
+    assert_eq!(count, SolutionCount::Many);
+    assert_eq!(solutions.len(), 3);
+}
+
+#[test]
+fn seeded_generation_deterministic() {
+    for seed in [0, 1, 42, u64::MAX] {
+        assert_eq!(
+            generate_sudoku(seed, Difficulty::Hard),
+            generate_sudoku(seed, Difficulty::Hard)
+        );
+    }
+}
+
+#[test]
+fn seeded_generation_golden() {
+    let expected = [
+        [9, 0, 4, 0, 5, 2, 0, 7, 0],
+        [0, 3, 7, 1, 0, 0, 2, 4, 5],
+        [0, 0, 2, 0, 4, 7, 0, 6, 8],
+        [3, 0, 6, 0, 0, 5, 4, 0, 0],
+        [0, 5, 0, 0, 0, 0, 6, 9, 0],
+        [1, 2, 9, 4, 6, 3, 0, 0, 0],
+        [0, 9, 0, 5, 1, 0, 7, 8, 0],
+        [0, 8, 0, 6, 2, 9, 0, 0, 0],
+        [6, 0, 0, 7, 0, 0, 5, 0, 9],
+    ];
+    assert_eq!(generate_sudoku(1234, Difficulty::Mid), expected);
+
+    let expected = [
+        [0, 1, 0, 3, 0, 0, 0, 2, 0],
+        [0, 0, 6, 0, 0, 0, 0, 8, 0],
+        [7, 8, 0, 0, 0, 0, 1, 0, 5],
+        [0, 0, 2, 0, 7, 0, 0, 0, 3],
+        [0, 0, 0, 0, 0, 0, 0, 0, 0],
+        [6, 0, 0, 0, 0, 2, 9, 0, 0],
+        [3, 0, 0, 0, 0, 0, 0, 0, 6],
+        [1, 0, 0, 0, 9, 8, 2, 0, 0],
+        [8, 0, 0, 2, 0, 5, 7, 0, 0],
+    ];
+    assert_eq!(generate_graded_sudoku(1234, Difficulty::Hard), expected);
+}
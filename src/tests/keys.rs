@@ -0,0 +1,48 @@
+use crate::keys::*;
+
+/// types `digits` with `preselection` selected, then the key of `action`.
+/// returns how often the action is repeated and the preselection it runs with.
+fn type_count(preselection: u8, digits: &str, action: Action) -> (usize, u8) {
+    let mut count = Count::default();
+    let mut preselection = preselection;
+    for digit in digits.bytes().map(|digit| digit - b'0') {
+        count.push(digit, preselection);
+        if digit != 0 {
+            preselection = digit;
+        }
+    }
+    let (times, restored) = count.take(Some(action));
+    assert!(count.is_empty());
+    (times, restored.unwrap_or(preselection))
+}
+
+#[test]
+fn single_digits_preselect() {
+    // `3l`, `4u` and `5<space>`
+    assert_eq!(type_count(1, "3", Action::MoveRight), (1, 3));
+    assert_eq!(type_count(1, "4", Action::Undo), (1, 4));
+    assert_eq!(type_count(1, "5", Action::Toggle), (1, 5));
+
+    // `5jjj<space>` places 5 three rows down
+    let mut count = Count::default();
+    count.push(5, 1);
+    assert_eq!(count.take(Some(Action::MoveDown)), (1, None));
+    assert_eq!(count.take(Some(Action::MoveDown)), (1, None));
+    assert_eq!(count.take(Some(Action::MoveDown)), (1, None));
+    assert_eq!(count.take(Some(Action::Toggle)), (1, None));
+}
+
+#[test]
+fn more_digits_count() {
+    assert_eq!(type_count(1, "03", Action::MoveRight), (3, 1));
+    assert_eq!(type_count(1, "10", Action::MoveDown), (10, 1));
+    assert_eq!(type_count(2, "04", Action::Undo), (4, 2));
+    assert_eq!(type_count(1, "12345", Action::Redo), (999, 1));
+
+    // the last digit before `<space>` is the number
+    assert_eq!(type_count(1, "35", Action::Toggle), (3, 5));
+    assert_eq!(type_count(1, "10", Action::Toggle), (10, 1));
+
+    // actions that can't be repeated drop the count
+    assert_eq!(type_count(1, "12", Action::Export), (1, 2));
+}
@@ -0,0 +1,60 @@
+use crate::{generator::*, logic::*, Board};
+
+/// walks through the logical solver's steps on generated sudokus
+/// and checks every deduction against the unique solution.
+#[test]
+fn logical_steps_agree_with_solution() {
+    let iterations = 20;
+
+    for _ in 0..iterations {
+        let mut board = generate_sudoku(random_seed(), Difficulty::Expert);
+        let (_, solutions) = count_solutions(&board, 2);
+        let solution = solutions[0];
+
+        let mut candidates = candidates(&board);
+        while let Some(step) = next_step(&candidates) {
+            for &(r, c, x) in &step.placements {
+                assert_eq!(solution[r][c], x, "wrong placement by {}", step);
+            }
+            for &(r, c, x) in &step.eliminations {
+                assert_ne!(solution[r][c], x, "wrong elimination by {}", step);
+            }
+            apply_step(&mut board, &mut candidates, &step);
+        }
+    }
+}
+
+#[test]
+fn grade_singles_only() {
+    let board: Board = [
+        [0, 0, 3, 0, 2, 0, 6, 0, 0],
+        [9, 0, 0, 3, 0, 5, 0, 0, 1],
+        [0, 0, 1, 8, 0, 6, 4, 0, 0],
+        [0, 0, 8, 1, 0, 2, 9, 0, 0],
+        [7, 0, 0, 0, 0, 0, 0, 0, 8],
+        [0, 0, 6, 7, 0, 8, 2, 0, 0],
+        [0, 0, 2, 6, 0, 9, 5, 0, 0],
+        [8, 0, 0, 2, 0, 3, 0, 0, 9],
+        [0, 0, 5, 0, 1, 0, 3, 0, 0],
+    ];
+    assert!(grade(&board) == Some(Difficulty::Easy));
+}
+
+#[test]
+fn grade_ambiguous() {
+    assert!(grade(&[[0; 9]; 9]).is_none());
+}
+
+#[test]
+fn generated_graded_sudoku_grades() {
+    use Difficulty::*;
+
+    for difficulty in [Easy, Mid, Hard, Expert] {
+        let sudoku = generate_graded_sudoku(random_seed(), difficulty);
+        assert!(
+            grade(&sudoku) == Some(difficulty),
+            "Generated sudoku is not graded as {}",
+            difficulty
+        );
+    }
+}
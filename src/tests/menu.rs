@@ -0,0 +1,105 @@
+use crate::{keys::*, menu::*, state::Mode, sudoku::Difficulty};
+
+/// presses the keys of `keys` in the menu with the default keybindings,
+/// returning the last choice made.
+fn press(menu: &mut Menu, keys: &[Key]) -> Option<MenuChoice> {
+    let keymap = Keymap::vim();
+    keys.iter()
+        .map(|&key| menu.handle(key, keymap.action(key, Mode::Edit)))
+        .last()
+        .flatten()
+}
+
+#[test]
+fn difficulties_are_chosen() {
+    let mut menu = Menu::new(false, Vec::new());
+    assert_eq!(
+        press(&mut menu, &[Key::Enter]),
+        Some(MenuChoice::New(Difficulty::Mid))
+    );
+
+    let choice = press(&mut menu, &[Key::Char('j'), Key::Down, Key::Char(' ')]);
+    assert_eq!(choice, Some(MenuChoice::New(Difficulty::Expert)));
+
+    let choice = press(
+        &mut menu,
+        &[Key::Char('k'), Key::Char('k'), Key::Up, Key::Enter],
+    );
+    assert_eq!(choice, Some(MenuChoice::New(Difficulty::Easy)));
+
+    // moving up from the first entry wraps around to quitting
+    assert_eq!(
+        press(&mut menu, &[Key::Up, Key::Enter]),
+        Some(MenuChoice::Quit)
+    );
+    assert_eq!(press(&mut menu, &[Key::Char('q')]), Some(MenuChoice::Quit));
+}
+
+#[test]
+fn custom_count_is_typed_or_changed() {
+    let mut menu = Menu::new(false, Vec::new());
+    press(&mut menu, &[Key::Char('4'), Key::Char('5')]);
+    assert_eq!(menu.current(), MenuItem::Custom);
+    assert_eq!(menu.custom_count, 45);
+
+    // counts above 81 start over with the last digit
+    press(&mut menu, &[Key::Char('9')]);
+    assert_eq!(menu.custom_count, 9);
+
+    let choice = press(&mut menu, &[Key::Char('L'), Key::Left, Key::Enter]);
+    assert_eq!(choice, Some(MenuChoice::New(Difficulty::Custom(18))));
+
+    press(&mut menu, &[Key::Char('H'), Key::Char('H'), Key::Char('H')]);
+    assert_eq!(menu.custom_count, 0);
+}
+
+#[test]
+fn files_are_typed_when_loading() {
+    let mut menu = Menu::new(true, Vec::new());
+    assert!(menu.items.contains(&MenuItem::Resume));
+    assert!(!Menu::new(false, Vec::new())
+        .items
+        .contains(&MenuItem::Resume));
+
+    menu.selected = menu
+        .items
+        .iter()
+        .position(|&item| item == MenuItem::Load)
+        .unwrap();
+    press(&mut menu, &[Key::Enter]);
+    assert_eq!(menu.input, Some(String::new()));
+
+    // keys bound to actions are typed into the file name
+    let keys = "puzzles.txtq".chars().map(Key::Char).collect::<Vec<_>>();
+    press(&mut menu, &keys);
+    let choice = press(&mut menu, &[Key::Backspace, Key::Enter]);
+    assert_eq!(choice, Some(MenuChoice::Load(String::from("puzzles.txt"))));
+    assert_eq!(menu.input, None);
+}
+
+#[test]
+fn highscores_are_paged_by_difficulty() {
+    let score = crate::scores::Score {
+        difficulty: Difficulty::Custom(20),
+        secs: 60,
+        date: String::from("2024-02-29"),
+        hints: 0,
+        mistakes: None,
+    };
+    let mut menu = Menu::new(false, vec![score]);
+    menu.selected = menu
+        .items
+        .iter()
+        .position(|&item| item == MenuItem::Scores)
+        .unwrap();
+
+    press(&mut menu, &[Key::Enter]);
+    assert_eq!(menu.scores_difficulty(), Some(Difficulty::Easy));
+    press(&mut menu, &[Key::Char('h')]);
+    assert_eq!(menu.scores_difficulty(), Some(Difficulty::Custom(20)));
+    press(&mut menu, &[Key::Right, Key::Char('l')]);
+    assert_eq!(menu.scores_difficulty(), Some(Difficulty::Mid));
+
+    assert_eq!(press(&mut menu, &[Key::Char('q')]), None);
+    assert_eq!(menu.scores_page, None);
+}
@@ -1,3 +1,13 @@
 #![cfg(test)]
+mod config;
+mod ex;
+mod format;
 mod generator;
+mod keys;
+mod logic;
+mod menu;
+mod save;
+mod scores;
+mod state;
+mod theme;
 mod validator;
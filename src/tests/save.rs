@@ -0,0 +1,98 @@
+use crate::{save::*, state::*, sudoku::*};
+
+use std::time::Duration;
+
+#[test]
+fn save_roundtrip() {
+    let board = generate_sudoku(7, Difficulty::Hard);
+    let mut state = State::init(board, Difficulty::Hard, Some(7));
+    state.graded = true;
+    state.highlight_conflicts = false;
+    state.check_mistakes = true;
+    state.max_mistakes = Some(3);
+    state.set_elapsed_time(Duration::from_secs(83)).unwrap();
+
+    let (row, col) = all_cells().find(|&(r, c)| board[r][c] == 0).unwrap();
+    state.move_cursor_to(row, col);
+    state.preselect_num(4);
+    state.toggle_current_mark();
+    state.preselect_num(6);
+    state.toggle_current_mark();
+    state.toggle_current_cell();
+    state.undo();
+    state.hint();
+    state.undo();
+    state.undo();
+    state.redo();
+    state.set_checkpoint('g');
+    state.enter_mode(Mode::Visual);
+    state.move_cursor_to(2, 3);
+    state.paint_selection();
+    state.fill_current_candidates();
+
+    let loaded = deserialize(&serialize(&state)).expect("failed to load saved game");
+
+    assert_eq!(loaded.board, state.board);
+    assert_eq!(loaded.modifiable, state.modifiable);
+    assert_eq!(loaded.markups, state.markups);
+    assert_eq!(loaded.colors, state.colors);
+    assert_eq!(loaded.undo_tree, state.undo_tree);
+    assert_eq!(loaded.checkpoints, state.checkpoints);
+    assert_eq!((loaded.cur_row, loaded.cur_col), (2, 3));
+    assert_eq!(loaded.preselection, 6);
+    assert_eq!(loaded.seed, Some(7));
+    assert!(loaded.graded);
+    assert_eq!(loaded.hints_used, 1);
+    assert_eq!(loaded.solution, state.solution);
+    assert_eq!(loaded.mistakes, state.mistakes);
+    assert_eq!(loaded.max_mistakes, Some(3));
+    assert!(!loaded.highlight_conflicts);
+    assert!(loaded.check_mistakes);
+    assert!(loaded.difficulty == Difficulty::Hard);
+    assert_eq!(loaded.get_timer_string(), "01:23");
+}
+
+#[test]
+fn load_rejects_other_versions() {
+    assert!(deserialize("shdoku-save 1\n").is_err());
+    assert!(deserialize("not a save file\n").is_err());
+}
+
+#[test]
+fn load_rejects_corrupt_cells() {
+    let state = State::init(generate_sudoku(7, Difficulty::Hard), Difficulty::Hard, None);
+    let text = serialize(&state);
+    assert!(deserialize(&text).is_ok());
+
+    let corrupt = |key: &str, value: &str| {
+        text.lines()
+            .map(|line| match line.split_once(' ') {
+                Some((k, _)) if k == key => format!("{key} {value}"),
+                _ => line.to_string(),
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+    assert!(deserialize(&corrupt("modifiable", "1,0,1")).is_err());
+    assert!(deserialize(&corrupt("modifiable", &["2"; 81].join(","))).is_err());
+    assert!(deserialize(&corrupt("marks", ",,12")).is_err());
+    assert!(deserialize(&corrupt("marks", &["1x"; 81].join(","))).is_err());
+}
+
+#[test]
+fn saved_game_is_recognized() {
+    let path = std::env::temp_dir().join(format!("shdoku-test-save-{}", std::process::id()));
+    let mut state = State::init(generate_sudoku(7, Difficulty::Hard), Difficulty::Hard, None);
+    write(&state, &path).unwrap();
+
+    state.preselect_num(3);
+    state.toggle_current_cell();
+    assert!(is_saved(&state, &path));
+    assert!(!is_saved(
+        &State::init(generate_sudoku(8, Difficulty::Hard), Difficulty::Hard, None),
+        &path
+    ));
+
+    std::fs::remove_file(&path).unwrap();
+    assert!(!is_saved(&state, &path));
+}
@@ -0,0 +1,105 @@
+use crate::{scores::*, state::State, sudoku::*};
+
+fn score(difficulty: Difficulty, secs: u64, hints: usize) -> Score {
+    Score {
+        difficulty,
+        secs,
+        date: String::from("2024-02-29"),
+        hints,
+        mistakes: None,
+    }
+}
+
+#[test]
+fn scores_roundtrip() {
+    let mut checked = score(Difficulty::Custom(45), 3725, 0);
+    checked.mistakes = Some(2);
+    let scores = vec![score(Difficulty::Hard, 301, 1), checked];
+
+    let text = serialize(&scores);
+    assert_eq!(deserialize(&text), Ok(scores));
+    assert!(deserialize("shdoku-scores 0\n").is_err());
+    assert!(deserialize("shdoku-scores 1\nscore hard 1 2024-02-29 x -\n").is_err());
+}
+
+#[test]
+fn best_scores_are_kept() {
+    let path = std::env::temp_dir().join(format!("shdoku-scores-{}", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+
+    for secs in (100..100 + TOP_COUNT as u64).rev() {
+        record(score(Difficulty::Mid, secs, 0), &path).unwrap();
+    }
+    // slower than all others, and tied with the slowest but recorded later
+    assert_eq!(record(score(Difficulty::Mid, 500, 0), &path), Ok(None));
+    let slowest = 100 + TOP_COUNT as u64 - 1;
+    assert_eq!(record(score(Difficulty::Mid, slowest, 0), &path), Ok(None));
+
+    // the same time with hints ranks after the one without
+    assert_eq!(record(score(Difficulty::Mid, 100, 3), &path), Ok(Some(2)));
+    assert_eq!(record(score(Difficulty::Easy, 900, 0), &path), Ok(Some(1)));
+
+    let scores = read(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    let mid = top(&scores, Difficulty::Mid);
+    assert_eq!(mid.len(), TOP_COUNT);
+    assert_eq!((mid[1].secs, mid[1].hints), (100, 3));
+    assert_eq!(mid[TOP_COUNT - 1].secs, slowest - 1);
+    assert_eq!(table(&scores, Difficulty::Easy).len(), 2);
+}
+
+#[test]
+fn unchecked_games_rank_after_checked_ones() {
+    let unchecked = score(Difficulty::Hard, 200, 0);
+    let mut flawless = score(Difficulty::Hard, 200, 0);
+    flawless.mistakes = Some(0);
+    let mut sloppy = score(Difficulty::Hard, 200, 0);
+    sloppy.mistakes = Some(5);
+
+    let scores = vec![unchecked.clone(), sloppy.clone(), flawless.clone()];
+    assert_eq!(
+        top(&scores, Difficulty::Hard),
+        vec![&flawless, &sloppy, &unchecked]
+    );
+}
+
+#[test]
+fn puzzles_without_blanks_get_no_score() {
+    let board = generate_sudoku(7, Difficulty::Custom(0));
+    assert_eq!(
+        Score::of(&State::init(board, Difficulty::Custom(0), None)),
+        None
+    );
+
+    let board = generate_sudoku(7, Difficulty::Easy);
+    let score = Score::of(&State::init(board, Difficulty::Easy, None)).unwrap();
+    assert_eq!(score.difficulty, Difficulty::Easy);
+}
+
+#[test]
+fn custom_difficulties_follow_named_ones() {
+    let scores = vec![
+        score(Difficulty::Custom(60), 1, 0),
+        score(Difficulty::Custom(20), 1, 0),
+        score(Difficulty::Mid, 1, 0),
+    ];
+    assert_eq!(
+        difficulties(&scores),
+        vec![
+            Difficulty::Easy,
+            Difficulty::Mid,
+            Difficulty::Hard,
+            Difficulty::Expert,
+            Difficulty::Custom(20),
+            Difficulty::Custom(60),
+        ]
+    );
+}
+
+#[test]
+fn dates_are_gregorian() {
+    assert_eq!(civil_date(0), (1970, 1, 1));
+    assert_eq!(civil_date(11016), (2000, 2, 29));
+    assert_eq!(civil_date(19782), (2024, 2, 29));
+    assert_eq!(civil_date(-1), (1969, 12, 31));
+}
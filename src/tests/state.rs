@@ -0,0 +1,340 @@
+use crate::{state::*, sudoku::*};
+
+/// returns a state for a generated sudoku with the cursor
+/// on its first blank cell, and the solution of that cell.
+fn state_on_blank_cell() -> (State, u8) {
+    let board = generate_sudoku(42, Difficulty::Mid);
+    let mut state = State::init(board, Difficulty::Mid, Some(42));
+    let (row, col) = all_cells().find(|&(r, c)| board[r][c] == 0).unwrap();
+    state.move_cursor_to(row, col);
+    let answer = state
+        .solution
+        .expect("generated sudoku has a unique solution")[row][col];
+    (state, answer)
+}
+
+#[test]
+fn solution_is_kept() {
+    let (state, _) = state_on_blank_cell();
+    assert!(is_solution(&state.solution.unwrap()));
+    assert_eq!(State::init_solution(state.get_puzzle()), state.solution);
+}
+
+#[test]
+fn mistakes_are_counted_when_checked() {
+    let (mut state, answer) = state_on_blank_cell();
+    let wrong = answer % 9 + 1;
+
+    state.preselect_num(wrong);
+    state.toggle_current_cell();
+    assert_eq!(state.mistakes, 0);
+    assert!(!state.is_mistake(state.cur_row, state.cur_col));
+
+    state.check_mistakes = true;
+    state.max_mistakes = Some(2);
+    state.delete_current_cell();
+    state.toggle_current_cell();
+    assert_eq!(state.mistakes, 1);
+    assert!(state.is_mistake(state.cur_row, state.cur_col));
+    assert!(!state.is_game_over());
+
+    state.preselect_num(answer);
+    state.toggle_current_cell();
+    assert_eq!(state.mistakes, 1);
+    assert!(!state.is_mistake(state.cur_row, state.cur_col));
+
+    state.preselect_num(wrong);
+    state.toggle_current_cell();
+    assert!(state.is_game_over());
+}
+
+#[test]
+fn filling_candidates_is_one_undo_step() {
+    let (mut state, answer) = state_on_blank_cell();
+    let candidates = logic::candidates(&state.board);
+
+    state.fill_all_candidates();
+    assert_eq!(state.undo_tree.current(), 1);
+    for (r, c) in all_cells() {
+        for num in 1..=9 {
+            let expected = state.board[r][c] == 0 && candidates[r][c] & (1 << num) != 0;
+            assert_eq!(state.markups[r][c][num - 1], expected);
+        }
+    }
+    assert!(state.markups[state.cur_row][state.cur_col][answer as usize - 1]);
+
+    state.undo();
+    assert_eq!(state.markups, [[[false; 9]; 9]; 9]);
+    state.redo();
+    assert!(state.markups[state.cur_row][state.cur_col][answer as usize - 1]);
+}
+
+#[test]
+fn hints_are_one_undo_step() {
+    let (mut state, _) = state_on_blank_cell();
+    let (board, markups) = (state.board, state.markups);
+
+    state.hint();
+    state.hint();
+    assert_eq!(state.undo_tree.current(), 2);
+    let (hinted_board, hinted_markups) = (state.board, state.markups);
+
+    state.undo();
+    state.undo();
+    assert_eq!((state.board, state.markups), (board, markups));
+
+    state.redo();
+    state.redo();
+    assert_eq!((state.board, state.markups), (hinted_board, hinted_markups));
+}
+
+#[test]
+fn hints_point_out_wrong_numbers_and_marks() {
+    let (mut state, answer) = state_on_blank_cell();
+    let (row, col) = (state.cur_row, state.cur_col);
+
+    // marks ruling out the answer are ignored
+    state.preselect_num(answer % 9 + 1);
+    state.toggle_current_mark();
+    assert!(state.markups[row][col][answer as usize % 9]);
+    for _ in 0..5 {
+        state.hint();
+    }
+    assert_eq!(state.mistakes, 0);
+    assert!(all_cells().all(|(r, c)| state.board[r][c] == 0
+        || Some(state.board[r][c]) == state.solution.map(|s| s[r][c])));
+
+    state.check_mistakes = true;
+    state.move_cursor_to(row, col);
+    state.delete_current_cell();
+    state.preselect_num(answer % 9 + 1);
+    state.toggle_current_cell();
+    let board = state.board;
+    let hints_used = state.hints_used;
+    state.hint();
+    assert_eq!(state.board, board);
+    assert_eq!(state.hints_used, hints_used + 1);
+    assert_eq!(
+        state.message,
+        format!("r{}c{} is wrong, fix it first", row + 1, col + 1)
+    );
+}
+
+#[test]
+fn hints_make_progress_past_wrong_marks() {
+    let board = generate_graded_sudoku(0, Difficulty::Expert);
+    let mut state = State::init(board, Difficulty::Expert, Some(0));
+    let solution = state.solution.unwrap();
+
+    // play up to the first elimination
+    let (row, col, x) = loop {
+        let step = next_step(&candidates(&state.board)).expect("no elimination needed");
+        if let Some(&elimination) = step.eliminations.first() {
+            break elimination;
+        }
+        state.hint();
+    };
+
+    // a mark ruling out the solution in the cell of the elimination
+    let wrong = (1..=9)
+        .find(|&num| num != x && num != solution[row][col])
+        .unwrap();
+    state.move_cursor_to(row, col);
+    state.preselect_num(wrong);
+    state.toggle_current_mark();
+
+    for _ in 0..2 {
+        let before = (state.board, state.markups);
+        state.hint();
+        let after = (state.board, state.markups);
+        assert!(after != before, "no progress: {}", state.message);
+    }
+    assert!(!state.markups[row][col][wrong as usize - 1]);
+}
+
+#[test]
+fn undo_restores_marks() {
+    let (mut state, answer) = state_on_blank_cell();
+    let (row, col) = (state.cur_row, state.cur_col);
+
+    state.fill_all_candidates();
+    let filled = state.markups;
+
+    // the inverse of a mark keeps its number, not the preselected one
+    state.enter_mode(Mode::Markup);
+    state.preselect_num(answer);
+    state.toggle_current_mark();
+    state.toggle_current_mark();
+    state.preselect_num(answer % 9 + 1);
+    state.undo();
+    state.undo();
+    state.redo();
+    state.redo();
+    assert_eq!(state.markups, filled);
+
+    // marks in both the row and the block of the cell are restored
+    state.preselect_num(answer);
+    state.toggle_current_cell();
+    assert!(!state.markups[row][(col + 1) % 9][answer as usize - 1]);
+    state.undo();
+    assert_eq!(state.markups, filled);
+    state.redo();
+    assert_eq!(state.board[row][col], answer);
+}
+
+#[test]
+fn undo_tree_keeps_branches() {
+    let (mut state, answer) = state_on_blank_cell();
+    let (row, col) = (state.cur_row, state.cur_col);
+    let wrong = answer % 9 + 1;
+
+    state.preselect_num(wrong);
+    state.toggle_current_cell();
+    state.undo();
+    state.preselect_num(answer);
+    state.toggle_current_cell();
+    assert_eq!(state.undo_tree.leaves(), vec![1, 2]);
+
+    // g- and g+ move through the changes in the order they were made
+    state.undo_chronologically();
+    assert_eq!(state.board[row][col], wrong);
+    state.undo_chronologically();
+    assert_eq!(state.board[row][col], 0);
+    state.redo_chronologically();
+    state.redo_chronologically();
+    assert_eq!(state.board[row][col], answer);
+
+    // redo follows the branch that was visited last
+    state.undo_chronologically();
+    state.undo();
+    state.redo();
+    assert_eq!(state.board[row][col], wrong);
+}
+
+#[test]
+fn checkpoints_restore_board_and_marks() {
+    let (mut state, answer) = state_on_blank_cell();
+    state.fill_all_candidates();
+    state.set_checkpoint('a');
+    let (board, markups) = (state.board, state.markups);
+
+    for _ in 0..5 {
+        state.hint();
+    }
+    state.preselect_num(answer % 9 + 1);
+    state.toggle_current_cell();
+    assert_ne!((state.board, state.markups), (board, markups));
+
+    state.jump_to_checkpoint('a');
+    assert_eq!((state.board, state.markups), (board, markups));
+
+    state.set_checkpoint('1');
+    assert!(!state.checkpoints.contains_key(&'1'));
+}
+
+#[test]
+fn dot_repeats_the_last_change_to_a_cell() {
+    let (mut state, _) = state_on_blank_cell();
+    let blanks = all_cells()
+        .filter(|&(r, c)| state.board[r][c] == 0)
+        .take(3)
+        .collect::<Vec<_>>();
+
+    state.enter_mode(Mode::Markup);
+    state.move_cursor_to(blanks[0].0, blanks[0].1);
+    state.preselect_num(1);
+    state.toggle_current_mark();
+    state.preselect_num(2);
+    state.toggle_current_mark();
+    assert_eq!(state.last_change.len(), 2);
+
+    for &(row, col) in &blanks[1..] {
+        state.move_cursor_to(row, col);
+        state.repeat_last_change();
+        assert!(state.markups[row][col][0] && state.markups[row][col][1]);
+    }
+
+    // each repetition is undone at once
+    state.undo();
+    let (row, col) = blanks[2];
+    assert!(!state.markups[row][col][0] && !state.markups[row][col][1]);
+
+    // a new change on another cell replaces the repeated one
+    state.preselect_num(3);
+    state.toggle_current_mark();
+    assert_eq!(state.last_change, vec![CellAction::Mark(3, true)]);
+}
+
+#[test]
+fn visual_mode_selects_rectangles() {
+    let (mut state, _) = state_on_blank_cell();
+    state.move_cursor_to(0, 0);
+    state.enter_mode(Mode::Visual);
+    state.move_cursor_to(1, 2);
+    state.stamp_selection();
+    state.move_cursor_to(8, 8);
+
+    let selection = state.get_selection();
+    let selected = all_cells().filter(|&(r, c)| selection[r][c]).count();
+    assert_eq!(selected, 6 + 8 * 7 - 1);
+    assert!(selection[0][2] && selection[4][4] && !selection[0][3]);
+
+    state.enter_mode(Mode::Edit);
+    assert_eq!(state.get_selection(), [[false; 9]; 9]);
+}
+
+#[test]
+fn visual_mode_actions_are_one_undo_step() {
+    let (mut state, _) = state_on_blank_cell();
+    state.move_cursor_to(0, 0);
+    state.enter_mode(Mode::Visual);
+    state.move_cursor_to(8, 8);
+    state.preselect_num(5);
+    state.mark_selection();
+    assert!(state.mode == Mode::Edit);
+    assert_eq!(state.undo_tree.current(), 1);
+    for (r, c) in all_cells() {
+        assert_eq!(state.markups[r][c][4], state.board[r][c] == 0);
+    }
+    let marked = state.markups;
+
+    state.enter_mode(Mode::Visual);
+    state.move_cursor_to(0, 0);
+    state.clear_selection_marks();
+    assert_eq!(state.markups, [[[false; 9]; 9]; 9]);
+
+    state.enter_mode(Mode::Visual);
+    state.move_cursor_to(8, 8);
+    state.paint_selection();
+    assert_eq!(state.colors, [[1; 9]; 9]);
+    state.enter_mode(Mode::Visual);
+    state.move_cursor_to(0, 0);
+    state.paint_selection();
+    assert_eq!(state.colors, [[2; 9]; 9]);
+
+    state.undo();
+    state.undo();
+    assert_eq!(state.colors, [[0; 9]; 9]);
+    state.undo();
+    assert_eq!(state.markups, marked);
+}
+
+#[test]
+fn counted_toggles_go_right_as_one_undo_step() {
+    let mut state = State::init([[0; 9]; 9], Difficulty::Custom(81), None);
+    state.move_cursor_to(2, 5);
+    state.preselect_num(7);
+
+    state.toggle_cells(6);
+    assert_eq!(state.board[2], [0, 0, 0, 0, 0, 7, 7, 7, 7]);
+    assert_eq!((state.cur_row, state.cur_col), (2, 8));
+
+    state.undo();
+    assert_eq!(state.board, [[0; 9]; 9]);
+
+    state.enter_mode(Mode::Markup);
+    state.move_cursor_to(0, 0);
+    state.toggle_cells(2);
+    assert!(state.markups[0][0][6] && state.markups[0][1][6]);
+    assert_eq!(state.board, [[0; 9]; 9]);
+}
@@ -0,0 +1,12 @@
+use crate::{keys::*, theme::*};
+
+#[test]
+fn theme_picker_cycles_through_themes() {
+    let mut picker = ThemePicker::new(&THEMES[THEMES.len() - 1]);
+    assert_eq!(picker.handle(Key::Down, None), None);
+    assert_eq!(picker.theme(), THEMES[0]);
+    assert_eq!(picker.handle(Key::Char('k'), Some(Action::MoveUp)), None);
+    assert_eq!(picker.theme(), THEMES[THEMES.len() - 1]);
+    assert_eq!(picker.handle(Key::Enter, None), Some(true));
+    assert_eq!(picker.handle(Key::Esc, Some(Action::EditMode)), Some(false));
+}
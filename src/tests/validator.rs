@@ -63,3 +63,48 @@ fn invalid_blocks_wrong() {
     ];
     assert!(!is_solution(&board));
 }
+
+#[test]
+fn conflicts_reported_with_positions() {
+    use crate::{find_conflicts, Unit};
+
+    let mut board = [[0; 9]; 9];
+    board[0][0] = 5;
+    board[0][8] = 5;
+    board[2][1] = 5;
+    board[4][4] = 7;
+
+    let conflicts = find_conflicts(&board);
+    assert_eq!(conflicts.len(), 2);
+    assert_eq!(conflicts[0].unit, Unit::Row(0));
+    assert_eq!(conflicts[0].cells, vec![(0, 0), (0, 8)]);
+    assert_eq!(conflicts[1].unit, Unit::Block(0));
+    assert_eq!(conflicts[1].cells, vec![(0, 0), (2, 1)]);
+    assert_eq!(conflicts[1].to_string(), "block 1 contains 5 at r1c1, r3c2");
+}
+
+#[test]
+fn validate_partial_boards() {
+    use crate::{validate, SolutionCount};
+
+    let mut board = [
+        [7, 6, 9, 5, 3, 8, 1, 2, 4],
+        [2, 4, 3, 7, 1, 9, 6, 5, 8],
+        [8, 5, 1, 4, 6, 2, 9, 7, 3],
+        [4, 8, 6, 9, 7, 5, 3, 1, 2],
+        [5, 3, 7, 6, 2, 1, 4, 8, 9],
+        [1, 9, 2, 8, 4, 3, 7, 6, 5],
+        [6, 1, 8, 3, 5, 4, 2, 9, 7],
+        [9, 7, 4, 2, 8, 6, 5, 3, 1],
+        [3, 2, 5, 1, 9, 7, 8, 4, 6],
+    ];
+    board[4][4] = 0;
+    let validation = validate(&board);
+    assert!(validation.conflicts.is_empty());
+    assert_eq!(validation.solutions, SolutionCount::One);
+
+    board[4][4] = 3;
+    let validation = validate(&board);
+    assert_eq!(validation.conflicts.len(), 3);
+    assert_eq!(validation.solutions, SolutionCount::Zero);
+}
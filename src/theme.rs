@@ -0,0 +1,147 @@
+use crate::keys::*;
+use crate::state::PAINT_COLOR_COUNT;
+
+use crossterm::style::Color;
+
+/// the colors the ui is drawn in.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Theme {
+    /// the name used to choose the theme in the config file
+    pub name: &'static str,
+    pub foreground: Color,
+    pub background: Color,
+    /// the color of the scoreboard values and the selected menu entries
+    pub accent: Color,
+    pub presel_color_pair: (Color, Color),
+    pub markup_color_background: Color,
+    pub conflict_color: Color,
+    pub mistake_color: Color,
+    pub selection_color_pair: (Color, Color),
+    pub paint_colors_background: [Color; PAINT_COLOR_COUNT as usize],
+}
+
+/// all themes, starting with the default one.
+pub const THEMES: [Theme; 4] = [
+    Theme {
+        name: "default",
+        foreground: Color::Reset,
+        background: Color::Reset,
+        accent: Color::Cyan,
+        presel_color_pair: (Color::Black, Color::Cyan),
+        markup_color_background: Color::Cyan,
+        conflict_color: Color::Red,
+        mistake_color: Color::Magenta,
+        selection_color_pair: (Color::Black, Color::Grey),
+        paint_colors_background: [Color::DarkBlue, Color::DarkGreen, Color::DarkYellow],
+    },
+    // dark highlights and pastel paint, readable on a light terminal background
+    Theme {
+        name: "light",
+        foreground: Color::Reset,
+        background: Color::Reset,
+        accent: Color::DarkBlue,
+        presel_color_pair: (Color::White, Color::DarkBlue),
+        markup_color_background: Color::AnsiValue(117),
+        conflict_color: Color::DarkRed,
+        mistake_color: Color::DarkMagenta,
+        selection_color_pair: (Color::White, Color::DarkGrey),
+        paint_colors_background: [
+            Color::AnsiValue(153),
+            Color::AnsiValue(194),
+            Color::AnsiValue(223),
+        ],
+    },
+    // blue, orange and yellow of the Okabe-Ito palette,
+    // never telling things apart by red and green alone
+    Theme {
+        name: "colorblind",
+        foreground: Color::Reset,
+        background: Color::Reset,
+        accent: Color::AnsiValue(39),
+        presel_color_pair: (Color::Black, Color::AnsiValue(39)),
+        markup_color_background: Color::AnsiValue(39),
+        conflict_color: Color::AnsiValue(208),
+        mistake_color: Color::AnsiValue(226),
+        selection_color_pair: (Color::Black, Color::Grey),
+        paint_colors_background: [
+            Color::AnsiValue(25),
+            Color::AnsiValue(130),
+            Color::AnsiValue(96),
+        ],
+    },
+    Theme {
+        name: "high-contrast",
+        foreground: Color::White,
+        background: Color::Black,
+        accent: Color::Yellow,
+        presel_color_pair: (Color::Black, Color::Yellow),
+        markup_color_background: Color::Yellow,
+        conflict_color: Color::Red,
+        mistake_color: Color::Magenta,
+        selection_color_pair: (Color::Black, Color::White),
+        paint_colors_background: [Color::Blue, Color::DarkGreen, Color::DarkMagenta],
+    },
+];
+
+impl Theme {
+    /// returns the theme called `name`.
+    pub fn by_name(name: &str) -> Result<Theme, String> {
+        THEMES
+            .iter()
+            .find(|theme| theme.name == name)
+            .copied()
+            .ok_or(format!(
+                "Unknown theme '{name}', use {}",
+                THEMES.map(|theme| theme.name).join(", ")
+            ))
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        THEMES[0]
+    }
+}
+
+/// state of the screen for choosing a theme, which is previewed while choosing.
+#[derive(Debug, PartialEq)]
+pub struct ThemePicker {
+    /// the index of the selected theme in `THEMES`
+    pub selected: usize,
+}
+
+impl ThemePicker {
+    /// returns the picker with `current` selected.
+    pub fn new(current: &Theme) -> Self {
+        ThemePicker {
+            selected: THEMES
+                .iter()
+                .position(|theme| theme.name == current.name)
+                .unwrap_or(0),
+        }
+    }
+
+    /// returns the selected theme.
+    pub fn theme(&self) -> Theme {
+        THEMES[self.selected]
+    }
+
+    /// handles a key press, with `action` being the action bound to `key`.
+    /// returns `Some(true)` if the selected theme was chosen,
+    /// and `Some(false)` if choosing was canceled.
+    pub fn handle(&mut self, key: Key, action: Option<Action>) -> Option<bool> {
+        let len = THEMES.len();
+        match (key, action) {
+            (Key::Up, _) | (_, Some(Action::MoveUp)) => {
+                self.selected = (self.selected + len - 1) % len
+            }
+            (Key::Down, _) | (_, Some(Action::MoveDown)) => {
+                self.selected = (self.selected + 1) % len
+            }
+            (Key::Enter, _) | (_, Some(Action::Toggle)) => return Some(true),
+            (Key::Esc, _) | (_, Some(Action::Quit)) => return Some(false),
+            _ => {}
+        }
+        None
+    }
+}
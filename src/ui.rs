@@ -1,4 +1,8 @@
+use crate::menu::*;
+use crate::scores;
 use crate::state::*;
+use crate::sudoku::Difficulty;
+use crate::theme::*;
 
 use std::cmp::Ordering::*;
 use std::io;
@@ -9,10 +13,11 @@ use crossterm::{
         SetCursorStyle,
     },
     execute, queue,
-    style::{Color, SetBackgroundColor, SetForegroundColor},
+    style::{ResetColor, SetBackgroundColor, SetForegroundColor},
     terminal::{
-        disable_raw_mode, enable_raw_mode, size, Clear, ClearType::All, EnterAlternateScreen,
-        LeaveAlternateScreen,
+        disable_raw_mode, enable_raw_mode, size, Clear,
+        ClearType::{All, UntilNewLine},
+        EnterAlternateScreen, LeaveAlternateScreen,
     },
 };
 
@@ -21,8 +26,7 @@ pub struct Ui<T>
 where
     T: io::Write,
 {
-    pub presel_color_pair: (Color, Color),
-    pub markup_color_background: Color,
+    pub theme: Theme,
     pub ostream: T,
     pub width: usize,
     pub height: usize,
@@ -32,21 +36,17 @@ impl<T> Ui<T>
 where
     T: io::Write,
 {
-    /// returns a new `Ui` using `ostream` as its' output stream
+    /// returns a new `Ui` using `ostream` as its' output stream, drawn in `theme`,
     /// and sets up terminal state:
     /// - remembers the current cursor position
     /// - enters alternate screen buffer
     /// - enables raw mode
-    pub fn init(ostream: T) -> Self {
+    pub fn init(ostream: T, theme: Theme) -> Self {
         let (width, height) = size().unwrap();
         let (width, height) = (width as usize, height as usize);
 
-        let presel_color_pair = (Color::Black, Color::Cyan);
-        let markup_color_background = Color::Cyan;
-
         let mut ui = Ui {
-            presel_color_pair,
-            markup_color_background,
+            theme,
             ostream,
             width,
             height,
@@ -64,7 +64,12 @@ where
     /// - restores cursor position
     pub fn deinit(&mut self) -> io::Result<()> {
         disable_raw_mode()?;
-        execute!(self.ostream, LeaveAlternateScreen, RestorePosition)?;
+        execute!(
+            self.ostream,
+            ResetColor,
+            LeaveAlternateScreen,
+            RestorePosition
+        )?;
         Ok(())
     }
 
@@ -72,28 +77,35 @@ where
     /// clears the screen and redraws the board if the dimensions changed.
     /// returns an error if the new dimensions are too small to fit the ui.
     pub fn update_dimensions(&mut self) -> io::Result<()> {
+        if self.check_dimensions()? {
+            self.clear()?;
+            self.draw_static_elements()?;
+        }
+
+        Ok(())
+    }
+
+    /// updates `width` and `height`, returning whether they changed.
+    /// returns an error if the new dimensions are too small to fit the ui.
+    fn check_dimensions(&mut self) -> io::Result<bool> {
         let old_dimensions = (self.width, self.height);
 
         let (width, height) = size()?;
         self.width = width as usize;
         self.height = height as usize;
 
-        if height < 14 || width < 54 {
+        if height < 17 || width < 54 {
             self.clear()?;
             self.deinit()?;
             eprintln!("[!]: Error: ui::update_dimensions: Terminal size too small to display UI.");
             return Err(io::Error::from(io::ErrorKind::Other));
         }
 
-        if old_dimensions != (self.width, self.height) {
-            self.clear()?;
-            self.draw_static_elements()?;
-        }
-
-        Ok(())
+        Ok(old_dimensions != (self.width, self.height))
     }
 
     pub fn clear(&mut self) -> io::Result<()> {
+        queue!(self.ostream, SetBackgroundColor(self.theme.background))?;
         queue!(self.ostream, Clear(All))?;
         queue!(self.ostream, MoveToColumn(0))
     }
@@ -112,6 +124,7 @@ where
 
         self.draw_numbers(state)?;
         self.draw_scoreboard(state)?;
+        self.draw_message(state)?;
         self.draw_cursor(state)?;
 
         self.ostream.flush()
@@ -125,8 +138,8 @@ where
     ///       in order to only have to flush once per frame.
     pub fn draw_static_elements(&mut self) -> io::Result<()> {
         self.init_cursor_offset()?;
-        queue!(self.ostream, SetForegroundColor(Color::Reset))?;
-        queue!(self.ostream, SetBackgroundColor(Color::Reset))?;
+        queue!(self.ostream, SetForegroundColor(self.theme.foreground))?;
+        queue!(self.ostream, SetBackgroundColor(self.theme.background))?;
 
         let board_template = board_template();
 
@@ -137,16 +150,265 @@ where
         Ok(())
     }
 
+    /// draws the start menu centered on the screen, with the selected entry
+    /// in the color of the preselection and a line for messages below it.
+    /// clears the screen first if its dimensions changed.
+    ///
+    /// NOTE: the game has to be drawn with `Ui::draw_static_elements()`
+    ///       after clearing the menu.
+    pub fn draw_menu(&mut self, menu: &Menu) -> io::Result<()> {
+        if self.check_dimensions()? {
+            self.clear()?;
+        }
+        if let Some(difficulty) = menu.scores_difficulty() {
+            return self.draw_scores(menu, difficulty);
+        }
+        let lft_pad = (self.width / 2 - 12) as u16;
+        let top_pad = (self.height / 2 - 8) as u16;
+        let mut y = top_pad;
+
+        queue!(self.ostream, SetForegroundColor(self.theme.foreground))?;
+        queue!(self.ostream, SetBackgroundColor(self.theme.background))?;
+        for line in [
+            "┌──────────────────────┐",
+            "│        shdoku        │",
+            "├──────────────────────┤",
+        ] {
+            queue!(self.ostream, MoveTo(lft_pad, y))?;
+            write!(self.ostream, "{}", line)?;
+            y += 1;
+        }
+
+        let mut cursor = (lft_pad, y);
+        for (i, &item) in menu.items.iter().enumerate() {
+            let (label, detail) = match item {
+                MenuItem::New(difficulty) => (
+                    difficulty.to_string(),
+                    difficulty.removal_count().to_string(),
+                ),
+                MenuItem::Custom => (
+                    String::from("Custom"),
+                    format!("< {:2} >", menu.custom_count),
+                ),
+                MenuItem::Resume => (String::from("Resume"), String::new()),
+                MenuItem::Load => (String::from("Load puzzle"), String::new()),
+                MenuItem::Scores => (String::from("Highscores"), String::new()),
+                MenuItem::Theme => (String::from("Theme"), String::new()),
+                MenuItem::Quit => (String::from("Quit"), String::new()),
+            };
+            let marker = match i == menu.selected {
+                true => {
+                    cursor = (lft_pad + 2, y);
+                    queue!(self.ostream, SetForegroundColor(self.theme.accent))?;
+                    '>'
+                }
+                false => ' ',
+            };
+
+            queue!(self.ostream, MoveTo(lft_pad, y))?;
+            write!(self.ostream, "│")?;
+            write!(self.ostream, " {} {:<11}{:>7} ", marker, label, detail)?;
+            queue!(self.ostream, SetForegroundColor(self.theme.foreground))?;
+            write!(self.ostream, "│")?;
+            y += 1;
+
+            if item == MenuItem::Custom {
+                queue!(self.ostream, MoveTo(lft_pad, y))?;
+                write!(self.ostream, "├──────────────────────┤")?;
+                y += 1;
+            }
+        }
+        queue!(self.ostream, MoveTo(lft_pad, y))?;
+        write!(self.ostream, "└──────────────────────┘")?;
+
+        let message = match &menu.input {
+            Some(file) => format!("File: {file}"),
+            None if menu.message.is_empty() => String::from("<enter> to choose, q to quit"),
+            None => menu.message.clone(),
+        };
+        let max_len = self.width - lft_pad as usize;
+        let message = message.chars().take(max_len).collect::<String>();
+        queue!(self.ostream, MoveTo(lft_pad, y + 2))?;
+        write!(self.ostream, "{}", message)?;
+        queue!(self.ostream, Clear(UntilNewLine))?;
+
+        match &menu.input {
+            Some(_) => {
+                let x = lft_pad + message.chars().count().min(max_len - 1) as u16;
+                queue!(self.ostream, MoveTo(x, y + 2), SetCursorStyle::SteadyBar)?;
+            }
+            None => {
+                queue!(
+                    self.ostream,
+                    MoveTo(cursor.0, cursor.1),
+                    SetCursorStyle::SteadyBlock
+                )?;
+            }
+        }
+
+        self.ostream.flush()
+    }
+
+    /// draws the highscore table of `difficulty` from the start menu.
+    fn draw_scores(&mut self, menu: &Menu, difficulty: Difficulty) -> io::Result<()> {
+        let lft_pad = (self.width / 2 - 21) as u16;
+        let top_pad = (self.height / 2 - 8) as u16;
+
+        let title = format!("Highscores: < {difficulty} >");
+        let mut lines = vec![
+            String::from("┌────────────────────────────────────────┐"),
+            format!("│{:^40}│", title),
+            String::from("├────────────────────────────────────────┤"),
+        ];
+        let table = scores::table(&menu.scores, difficulty);
+        for i in 0..=scores::TOP_COUNT {
+            let row = table.get(i).map(String::as_str).unwrap_or("");
+            lines.push(format!("│ {:<38} │", row));
+        }
+        lines.push(String::from("└────────────────────────────────────────┘"));
+        lines.push(String::new());
+        lines.push(String::from(
+            "h, l to change the difficulty, <esc> to go back",
+        ));
+
+        queue!(self.ostream, SetForegroundColor(self.theme.foreground))?;
+        queue!(self.ostream, SetBackgroundColor(self.theme.background))?;
+        for (y, line) in lines.iter().enumerate() {
+            queue!(self.ostream, MoveTo(lft_pad, top_pad + y as u16))?;
+            let line = line
+                .chars()
+                .take(self.width - lft_pad as usize)
+                .collect::<String>();
+            write!(self.ostream, "{}", line)?;
+        }
+        queue!(
+            self.ostream,
+            MoveTo(lft_pad + 2, top_pad + 1),
+            SetCursorStyle::SteadyBlock
+        )?;
+
+        self.ostream.flush()
+    }
+
+    /// draws the screen for choosing a theme, with a row of cells
+    /// previewing its highlights below the themes.
+    /// switches `theme` to the selected one, clearing the screen if it changed.
+    ///
+    /// NOTE: the previous theme has to be restored by the caller
+    ///       if choosing is canceled.
+    pub fn draw_theme_picker(&mut self, picker: &ThemePicker) -> io::Result<()> {
+        let changed = self.theme != picker.theme();
+        self.theme = picker.theme();
+        if self.check_dimensions()? || changed {
+            self.clear()?;
+        }
+        let lft_pad = (self.width / 2 - 13) as u16;
+        let top_pad = (self.height / 2 - 8) as u16;
+        let mut y = top_pad;
+
+        queue!(self.ostream, SetForegroundColor(self.theme.foreground))?;
+        queue!(self.ostream, SetBackgroundColor(self.theme.background))?;
+        for line in [
+            "┌────────────────────────┐",
+            "│         Themes         │",
+            "├────────────────────────┤",
+        ] {
+            queue!(self.ostream, MoveTo(lft_pad, y))?;
+            write!(self.ostream, "{}", line)?;
+            y += 1;
+        }
+
+        for (i, theme) in THEMES.iter().enumerate() {
+            queue!(self.ostream, MoveTo(lft_pad, y))?;
+            write!(self.ostream, "│")?;
+            let marker = match i == picker.selected {
+                true => {
+                    queue!(self.ostream, SetForegroundColor(self.theme.accent))?;
+                    '>'
+                }
+                false => ' ',
+            };
+            write!(self.ostream, " {} {:<20} ", marker, theme.name)?;
+            queue!(self.ostream, SetForegroundColor(self.theme.foreground))?;
+            write!(self.ostream, "│")?;
+            y += 1;
+        }
+
+        queue!(self.ostream, MoveTo(lft_pad, y))?;
+        write!(self.ostream, "├────────────────────────┤")?;
+        queue!(self.ostream, MoveTo(lft_pad, y + 1))?;
+        write!(self.ostream, "│")?;
+
+        // a preselected number, a mark, a conflict, a mistake,
+        // a selected cell and the paint colors
+        let theme = self.theme;
+        let preview = [
+            (theme.presel_color_pair.0, theme.presel_color_pair.1, '5'),
+            (theme.foreground, theme.markup_color_background, ' '),
+            (theme.conflict_color, theme.background, '4'),
+            (theme.mistake_color, theme.background, '2'),
+            (
+                theme.selection_color_pair.0,
+                theme.selection_color_pair.1,
+                '8',
+            ),
+            (theme.foreground, theme.paint_colors_background[0], '1'),
+            (theme.foreground, theme.paint_colors_background[1], '7'),
+            (theme.foreground, theme.paint_colors_background[2], '3'),
+        ];
+        for (foreground, background, chr) in preview {
+            queue!(
+                self.ostream,
+                SetForegroundColor(foreground),
+                SetBackgroundColor(background)
+            )?;
+            write!(self.ostream, " {} ", chr)?;
+        }
+        queue!(self.ostream, SetForegroundColor(self.theme.foreground))?;
+        queue!(self.ostream, SetBackgroundColor(self.theme.background))?;
+        write!(self.ostream, "│")?;
+        queue!(self.ostream, MoveTo(lft_pad, y + 2))?;
+        write!(self.ostream, "└────────────────────────┘")?;
+
+        let help = "<enter> to keep, <esc> to cancel";
+        let help = help
+            .chars()
+            .take(self.width - lft_pad as usize)
+            .collect::<String>();
+        queue!(self.ostream, MoveTo(lft_pad, y + 4))?;
+        write!(self.ostream, "{}", help)?;
+        queue!(self.ostream, Clear(UntilNewLine))?;
+
+        let cursor_y = top_pad + 3 + picker.selected as u16;
+        queue!(
+            self.ostream,
+            MoveTo(lft_pad + 2, cursor_y),
+            SetCursorStyle::SteadyBlock
+        )?;
+
+        self.ostream.flush()
+    }
+
     /// `queue!(...)`s the drawing of the numbers in the cells.
+    /// numbers that break the sudoku rules are drawn in `conflict_color`,
+    /// and numbers disagreeing with the solution in `mistake_color`,
+    /// unless the player turned that off.
+    /// cells selected in Visual mode are drawn in `selection_color_pair`,
+    /// painted cells that are not highlighted otherwise in their paint color.
     ///
     /// NOTE: this function itself does not flush to `self.ostream`
     ///       in order to only have to flush once per frame.
+    #[allow(clippy::needless_range_loop)]
     fn draw_numbers(&mut self, state: &State) -> io::Result<()> {
         self.init_cursor_offset()?;
-        queue!(self.ostream, SetForegroundColor(Color::Reset))?;
-        queue!(self.ostream, SetBackgroundColor(Color::Reset))?;
+        queue!(self.ostream, SetForegroundColor(self.theme.foreground))?;
+        queue!(self.ostream, SetBackgroundColor(self.theme.background))?;
         self.move_cursor_by(1, 0)?;
 
+        let conflicts = state.get_conflict_mask();
+        let mistakes = state.get_mistake_mask();
+        let selection = state.get_selection();
+
         for row in 0..9 {
             match row {
                 3 | 6 => self.move_cursor_by(0, 2),
@@ -163,8 +425,8 @@ where
                     x if x == state.preselection => {
                         queue!(
                             self.ostream,
-                            SetForegroundColor(self.presel_color_pair.0),
-                            SetBackgroundColor(self.presel_color_pair.1)
+                            SetForegroundColor(self.theme.presel_color_pair.0),
+                            SetBackgroundColor(self.theme.presel_color_pair.1)
                         )
                         .unwrap_or(());
                         (x + b'0') as char
@@ -174,25 +436,47 @@ where
                             true => {
                                 queue!(
                                     self.ostream,
-                                    SetBackgroundColor(self.markup_color_background)
+                                    SetBackgroundColor(self.theme.markup_color_background)
                                 )?;
                             }
                             false => {
                                 queue!(
                                     self.ostream,
-                                    SetForegroundColor(Color::Reset),
-                                    SetBackgroundColor(Color::Reset)
+                                    SetForegroundColor(self.theme.foreground),
+                                    SetBackgroundColor(self.theme.background)
                                 )?;
                             }
                         }
                         ' '
                     }
                     x => {
-                        queue!(self.ostream, SetForegroundColor(Color::Reset)).unwrap_or(());
-                        queue!(self.ostream, SetBackgroundColor(Color::Reset)).unwrap_or(());
+                        queue!(self.ostream, SetForegroundColor(self.theme.foreground))
+                            .unwrap_or(());
+                        queue!(self.ostream, SetBackgroundColor(self.theme.background))
+                            .unwrap_or(());
                         (x + b'0') as char
                     }
                 };
+                let num = state.board[row][col];
+                let highlighted = num == state.preselection
+                    || (num == 0 && state.markups[row][col][state.preselection as usize - 1]);
+                let color = state.colors[row][col];
+                if selection[row][col] {
+                    queue!(
+                        self.ostream,
+                        SetForegroundColor(self.theme.selection_color_pair.0),
+                        SetBackgroundColor(self.theme.selection_color_pair.1)
+                    )?;
+                } else if color != 0 && !highlighted {
+                    let background = self.theme.paint_colors_background[color as usize - 1];
+                    queue!(self.ostream, SetBackgroundColor(background))?;
+                }
+
+                if mistakes[row][col] {
+                    queue!(self.ostream, SetForegroundColor(self.theme.mistake_color))?;
+                } else if conflicts[row][col] {
+                    queue!(self.ostream, SetForegroundColor(self.theme.conflict_color))?;
+                }
                 write!(self.ostream, "{}", chr)?;
             }
 
@@ -207,14 +491,14 @@ where
     ///       in order to only have to flush once per frame.
     fn draw_scoreboard(&mut self, state: &State) -> io::Result<()> {
         self.init_cursor_offset()?;
-        queue!(self.ostream, SetForegroundColor(Color::Reset))?;
-        queue!(self.ostream, SetBackgroundColor(Color::Reset))?;
+        queue!(self.ostream, SetForegroundColor(self.theme.foreground))?;
+        queue!(self.ostream, SetBackgroundColor(self.theme.background))?;
 
         self.move_cursor_by(34, 1)?;
         write!(self.ostream, "{}", state.get_difficulty_string())?;
 
         self.move_cursor_by(-5, 1)?;
-        queue!(self.ostream, SetForegroundColor(self.presel_color_pair.1))?;
+        queue!(self.ostream, SetForegroundColor(self.theme.accent))?;
         write!(self.ostream, "{}", state.get_completion_string())?;
 
         self.move_cursor_by(-2, 2)?;
@@ -222,24 +506,25 @@ where
         write!(self.ostream, "{}", mins_string)?;
         self.move_cursor_by(1, 0)?;
         write!(self.ostream, "{}", secs_string)?;
-        queue!(self.ostream, SetForegroundColor(Color::Reset))?;
+        queue!(self.ostream, SetForegroundColor(self.theme.foreground))?;
 
         self.move_cursor_by(-6, 2)?;
-        for _ in 0..3 {
+        for _ in 0..4 {
             write!(self.ostream, " ")?;
             self.move_cursor_by(-1, 1)?;
         }
 
-        self.move_cursor_by(0, -3)?;
+        self.move_cursor_by(0, -4)?;
 
         let selected_mode_idx = match state.mode {
             Mode::Edit => 0,
             Mode::Markup => 1,
             Mode::Go => 2,
+            Mode::Visual => 3,
         };
 
-        queue!(self.ostream, SetForegroundColor(self.presel_color_pair.1))?;
-        for i in 0..3 {
+        queue!(self.ostream, SetForegroundColor(self.theme.accent))?;
+        for i in 0..4 {
             if i == selected_mode_idx {
                 write!(self.ostream, ">")?;
             }
@@ -252,17 +537,48 @@ where
         self.move_cursor_by(-3, 1)?;
         write!(self.ostream, "{}", state.get_preselection_completion_char())?;
 
-        queue!(self.ostream, SetForegroundColor(Color::Reset))?;
+        self.move_cursor_by(-1, 2)?;
+        write!(self.ostream, "{}", state.get_mistakes_string())?;
+
+        queue!(self.ostream, SetForegroundColor(self.theme.foreground))?;
         Ok(())
     }
 
-    /// `queue!(...)`s the placement of the cursor on the selected cell.
+    /// `queue!(...)`s the drawing of `state.message` below the board,
+    /// or the command line instead while a command is typed.
+    /// the message is cut off at the edge of the screen.
+    ///
+    /// NOTE: this function itself does not flush to `self.ostream`
+    ///       in order to only have to flush once per frame.
+    fn draw_message(&mut self, state: &State) -> io::Result<()> {
+        self.init_cursor_offset()?;
+        self.move_cursor_by(0, 16)?;
+
+        let max_len = self.width - self.width / 2 + 14;
+        let message = match &state.command_line {
+            Some(line) => format!(":{line}"),
+            None => state.message.clone(),
+        };
+        let message = message.chars().take(max_len).collect::<String>();
+        write!(self.ostream, "{}", message)?;
+        queue!(self.ostream, Clear(UntilNewLine))
+    }
+
+    /// `queue!(...)`s the placement of the cursor on the selected cell,
+    /// or at the end of the command line while a command is typed.
     ///
     /// NOTE: this function itself does not flush to `self.ostream`
     ///       in order to only have to flush once per frame.
     fn draw_cursor(&mut self, state: &State) -> io::Result<()> {
+        if let Some(line) = &state.command_line {
+            let max_len = self.width - self.width / 2 + 14;
+            let x = (self.width / 2 - 14 + (line.chars().count() + 1).min(max_len - 1)) as u16;
+            let y = (self.height / 2 - 8 + 16) as u16;
+            return queue!(self.ostream, MoveTo(x, y), SetCursorStyle::SteadyBar);
+        }
+
         let (row, col) = (state.cur_row, state.cur_col);
-        let (x, y) = ((self.width / 2 - 14) as u16, (self.height / 2 - 6) as u16);
+        let (x, y) = ((self.width / 2 - 14) as u16, (self.height / 2 - 8) as u16);
         let (x, y) = (x + 2, y + 1);
         let (x, y) = (x + 2 * col as u16, y + row as u16);
         let x = match col {
@@ -304,14 +620,14 @@ where
     /// since the padding depends on the screen dimensions.
     fn init_cursor_offset(&mut self) -> io::Result<()> {
         let lft_pad = (self.width / 2 - 14) as u16;
-        let top_pad = (self.height / 2 - 6) as u16;
+        let top_pad = (self.height / 2 - 8) as u16;
         queue!(self.ostream, MoveTo(lft_pad, top_pad))
     }
 }
 
 /// returns a template for the parts of the board that
 /// are always the same.
-fn board_template() -> [String; 13] {
+fn board_template() -> [String; 16] {
     [
         String::from("┌────────┬────────┬────────┐    ┌───────┐"),
         String::from("│        │        │        │    │       │"),
@@ -322,10 +638,13 @@ fn board_template() -> [String; 13] {
         String::from("│        │        │        │    │  Edit │"),
         String::from("│        │        │        │    │  Mark │"),
         String::from("├────────┼────────┼────────┤    │  Go   │"),
+        String::from("│        │        │        │    │  Vis  │"),
         String::from("│        │        │        │    ├───────┤"),
         String::from("│        │        │        │    │  [ ]  │"),
-        String::from("│        │        │        │    │   / 9 │"),
-        String::from("└────────┴────────┴────────┘    └───────┘"),
+        String::from("└────────┴────────┴────────┘    │   / 9 │"),
+        String::from("                                ├───────┤"),
+        String::from("                                │✗      │"),
+        String::from("                                └───────┘"),
     ]
 }
 
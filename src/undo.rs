@@ -0,0 +1,208 @@
+use crate::state::{DiffStep, DiffType};
+
+/// a tree of all changes made to the board, like vim's undo tree.
+/// making a change after undoing others starts a new branch
+/// instead of throwing the undone changes away.
+///
+/// node `0` is the untouched puzzle, every other node is the change with
+/// the same number, in the order the changes were made.
+///
+/// NOTE: the steps of the nodes between the root and `current` are stored
+///       as undo steps, all other steps as redo steps.
+///       `State::apply_step()` inverts a step whenever it is applied.
+#[derive(Debug, PartialEq, Clone)]
+pub struct UndoTree {
+    nodes: Vec<UndoNode>,
+    current: usize,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+struct UndoNode {
+    parent: usize,
+    step: DiffStep,
+    children: Vec<usize>,
+    /// the child that is redone from this node.
+    /// this is the most recently visited one.
+    redo_child: Option<usize>,
+}
+
+impl UndoTree {
+    pub fn new() -> Self {
+        Self {
+            nodes: vec![UndoNode {
+                parent: 0,
+                step: DiffStep::Group(Vec::new()),
+                children: Vec::new(),
+                redo_child: None,
+            }],
+            current: 0,
+        }
+    }
+
+    /// returns the number the next change will get.
+    pub fn next_node(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// returns the number of the change the board is at.
+    pub fn current(&self) -> usize {
+        self.current
+    }
+
+    /// adds a change made to the board at `current` as a new child of it
+    /// and moves to that change.
+    /// `step` has to be the step undoing the change.
+    pub fn push(&mut self, step: DiffStep) {
+        let node = self.nodes.len();
+        self.nodes.push(UndoNode {
+            parent: self.current,
+            step,
+            children: Vec::new(),
+            redo_child: None,
+        });
+        self.nodes[self.current].children.push(node);
+        self.nodes[self.current].redo_child = Some(node);
+        self.current = node;
+    }
+
+    /// combines the changes numbered `node` and above into a single change
+    /// of `DiffStep::Group`.
+    /// these have to be the last changes made, on a single branch.
+    pub fn group_since(&mut self, node: usize) {
+        if self.nodes.len() <= node + 1 {
+            return;
+        }
+        let grouped = self.nodes.split_off(node);
+        let parent = grouped[0].parent;
+
+        self.nodes[parent].children.retain(|&child| child != node);
+        self.current = parent;
+        self.push(DiffStep::Group(
+            grouped.into_iter().map(|node| node.step).collect(),
+        ));
+    }
+
+    /// returns the node to move to for undoing the current change.
+    pub fn undo_target(&self) -> Option<usize> {
+        match self.current {
+            0 => None,
+            node => Some(self.nodes[node].parent),
+        }
+    }
+
+    /// returns the node to move to for redoing the last undone change.
+    pub fn redo_target(&self) -> Option<usize> {
+        self.nodes[self.current].redo_child
+    }
+
+    /// returns the steps to apply to move from `current` to `target`:
+    /// undoing changes up to the last common ancestor,
+    /// then redoing changes down to `target`.
+    pub fn path(&self, target: usize) -> Vec<(usize, DiffType)> {
+        let from = self.ancestors(self.current);
+        let to = self.ancestors(target);
+        let common = *from.iter().find(|node| to.contains(node)).unwrap_or(&0);
+
+        let undos = from.into_iter().take_while(|&node| node != common);
+        let mut redos = to
+            .into_iter()
+            .take_while(|&node| node != common)
+            .collect::<Vec<_>>();
+        redos.reverse();
+
+        undos
+            .map(|node| (node, DiffType::Undo))
+            .chain(redos.into_iter().map(|node| (node, DiffType::Redo)))
+            .collect()
+    }
+
+    /// returns `node` followed by its ancestors up to and including the root.
+    fn ancestors(&self, mut node: usize) -> Vec<usize> {
+        let mut ancestors = vec![node];
+        while node != 0 {
+            node = self.nodes[node].parent;
+            ancestors.push(node);
+        }
+        ancestors
+    }
+
+    /// replaces the step of `node` with `step`, returning the old one.
+    pub fn replace_step(&mut self, node: usize, step: DiffStep) -> DiffStep {
+        std::mem::replace(&mut self.nodes[node].step, step)
+    }
+
+    /// moves to `node`, making the branch leading to it the one that is redone.
+    ///
+    /// NOTE: this does not apply any steps. (see `path()`)
+    pub fn set_current(&mut self, mut node: usize) {
+        self.current = node;
+        while node != 0 {
+            let parent = self.nodes[node].parent;
+            self.nodes[parent].redo_child = Some(node);
+            node = parent;
+        }
+    }
+
+    /// returns the changes at the tips of the branches, in the order they were made.
+    pub fn leaves(&self) -> Vec<usize> {
+        (0..self.nodes.len())
+            .filter(|&node| self.nodes[node].children.is_empty())
+            .collect()
+    }
+
+    /// returns the number of changes between the root and `node`.
+    pub fn depth(&self, node: usize) -> usize {
+        self.ancestors(node).len() - 1
+    }
+
+    /// returns all nodes as `(parent, redo_child, step)`, starting with the root.
+    /// `redo_child` is `0` if there is none.
+    /// (see `from_nodes()`)
+    pub fn nodes(&self) -> impl Iterator<Item = (usize, usize, &DiffStep)> {
+        self.nodes
+            .iter()
+            .map(|node| (node.parent, node.redo_child.unwrap_or(0), &node.step))
+    }
+
+    /// rebuilds a tree from the nodes returned by `nodes()`,
+    /// positioned at `current`.
+    pub fn from_nodes(
+        nodes: Vec<(usize, usize, DiffStep)>,
+        current: usize,
+    ) -> Result<Self, String> {
+        let mut tree = UndoTree {
+            nodes: Vec::with_capacity(nodes.len()),
+            current,
+        };
+
+        for (node, (parent, _, step)) in nodes.iter().cloned().enumerate() {
+            if node > 0 && parent >= node {
+                return Err(format!("Invalid parent {parent} of undo step {node}."));
+            }
+            if node > 0 {
+                tree.nodes[parent].children.push(node);
+            }
+            tree.nodes.push(UndoNode {
+                parent,
+                step,
+                children: Vec::new(),
+                redo_child: None,
+            });
+        }
+
+        for (node, &(_, redo_child, _)) in nodes.iter().enumerate() {
+            if redo_child != 0 {
+                if !tree.nodes[node].children.contains(&redo_child) {
+                    return Err(format!("Invalid redo step {redo_child}."));
+                }
+                tree.nodes[node].redo_child = Some(redo_child);
+            }
+        }
+
+        if current >= tree.nodes.len() {
+            return Err(format!("Invalid current undo step {current}."));
+        }
+
+        Ok(tree)
+    }
+}